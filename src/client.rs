@@ -1,14 +1,16 @@
 use crate::game::{Move, State, Team};
-use crate::protocol::{Event, EventPayload, GameResult, Request, RequestPayload};
-use crate::util::{Element, SCError, SCResult};
-use log::{debug, error, info, warn};
+use crate::protocol::{Event, EventPayload, GameResult, MatchOutcome, Request, RequestPayload};
+use crate::util::{verbose_debug, Element, SCError, SCResult};
+use log::{error, info, warn};
 use quick_xml::events::{BytesEnd, BytesStart, Event as XmlEvent};
 use quick_xml::{Reader, Writer};
+use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
 use std::convert::TryFrom;
 use std::io::{self, BufReader, BufWriter, Read, Write};
-use std::net::TcpStream;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// A handler that implements the game player's
 /// behavior, usually employing some custom move
@@ -17,26 +19,87 @@ pub trait SCClientDelegate {
     /// Invoked whenever the game state updates.
     fn on_update_state(&mut self, _state: &State) {}
 
-    /// Invoked when the game ends.
-    fn on_game_end(&mut self, _result: &GameResult, _my_team: Team) {}
+    /// Invoked when the game ends with the result and how
+    /// it turned out for the player, which allows telling
+    /// a draw apart from a loss.
+    fn on_game_end(&mut self, _result: &GameResult, _my_team: Team, _outcome: MatchOutcome) {}
 
     /// Invoked when the welcome message is received
     /// with the player's team.
     fn on_welcome(&mut self, _team: Team) {}
 
+    /// Invoked when the game is paused or unpaused. While
+    /// paused, the server will not request any moves.
+    fn on_pause_changed(&mut self, _paused: bool) {}
+
+    /// Invoked after the handshake with a handle that
+    /// can be used to send arbitrary requests to the server.
+    fn on_connect(&mut self, _sender: RequestSender) {}
+
     /// Requests a move from the delegate. This method
     /// should implement the "main" game logic.
     fn request_move(&mut self, state: &State, my_team: Team) -> Move;
+
+    /// Requests a move from the delegate that should be chosen
+    /// within the given time budget, e.g. to stop an iterative
+    /// deepening search in time. The 2022 server does not send a
+    /// budget with its move requests, so this is the one configured
+    /// using `SCClientBuilder::move_budget`. By default, this
+    /// ignores the budget and calls `request_move`.
+    fn request_move_within(&mut self, state: &State, my_team: Team, _budget: Duration) -> Move {
+        self.request_move(state, my_team)
+    }
+
+    /// Invoked with the move returned by `request_move`
+    /// right before it is sent, e.g. to log training data.
+    fn on_move_chosen(&mut self, _state: &State, _chosen: &Move) {}
+
+    /// Invoked with the time the delegate took to choose
+    /// its last move, e.g. for tuning the time management.
+    fn on_move_timing(&mut self, _duration: Duration) {}
 }
 
+/// The major version of the server (i.e. the season) whose
+/// protocol this client implements.
+pub const PROTOCOL_VERSION: u32 = 22;
+
+/// The default time budget for choosing a move. The server
+/// allows two seconds per move, part of which is reserved
+/// for the network round trip.
+pub const DEFAULT_MOVE_BUDGET: Duration = Duration::from_millis(1800);
+
 /// A configuration that determines whether
 /// the reader and/or the writer of a stream
 /// should be swapped by stdio to ease debugging.
+#[derive(Debug, Default, Clone, Copy)]
 pub struct DebugMode {
     pub debug_reader: bool,
     pub debug_writer: bool,
 }
 
+/// A handle for sending arbitrary requests to the server,
+/// e.g. from within a delegate. Queued requests are written
+/// once the client has handled the current server message.
+#[derive(Debug, Clone)]
+pub struct RequestSender(Sender<Request>);
+
+impl RequestSender {
+    /// Queues the given request for sending.
+    pub fn send(&self, request: Request) {
+        // The receiver lives as long as the client, which
+        // outlives any game it could send requests in.
+        let _ = self.0.send(request);
+    }
+
+    /// Queues the given requests for sending in order. Each
+    /// request is flushed to the server as soon as it is written.
+    pub fn send_all(&self, requests: impl IntoIterator<Item = Request>) {
+        for request in requests {
+            self.send(request);
+        }
+    }
+}
+
 /// The client which handles XML requests, manages
 /// the game state and invokes the delegate.
 pub struct SCClient<D>
@@ -46,7 +109,38 @@ where
     delegate: D,
     debug_mode: DebugMode,
     reservation_code: Option<String>,
+    shutdown_delay: Duration,
+    retain_states: bool,
+    preamble: Vec<u8>,
+    nodelay: bool,
+    keepalive: Option<Duration>,
+    local_address: Option<SocketAddr>,
+    protocol_version: u32,
+    move_budget: Duration,
+    server_version: Option<String>,
+    states: Vec<State>,
     client_team: Option<Team>, // TODO: Add game state
+    request_sender: Sender<Request>,
+    request_receiver: Receiver<Request>,
+}
+
+/// A builder that makes the configuration of
+/// new clients more convenient.
+pub struct SCClientBuilder<D>
+where
+    D: SCClientDelegate,
+{
+    delegate: D,
+    debug_mode: DebugMode,
+    reservation_code: Option<String>,
+    shutdown_delay: Duration,
+    retain_states: bool,
+    preamble: Vec<u8>,
+    nodelay: bool,
+    keepalive: Option<Duration>,
+    local_address: Option<SocketAddr>,
+    protocol_version: u32,
+    move_budget: Duration,
 }
 
 impl<D> SCClient<D>
@@ -55,70 +149,137 @@ where
 {
     /// Creates a new client using the specified delegate.
     pub fn new(delegate: D, debug_mode: DebugMode, reservation_code: Option<String>) -> Self {
-        Self {
-            delegate,
-            debug_mode,
-            reservation_code,
-            client_team: None,
-        }
+        Self::builder(delegate)
+            .debug_mode(debug_mode)
+            .reservation(reservation_code)
+            .build()
+    }
+
+    /// Creates a new client builder using the specified delegate.
+    pub fn builder(delegate: D) -> SCClientBuilder<D> {
+        SCClientBuilder::new(delegate)
     }
 
     /// Blocks the thread and begins reading XML messages
-    /// from the provided address via TCP.
+    /// from the provided address via TCP. The host may be a
+    /// hostname, an IPv4 literal or an IPv6 literal (with or
+    /// without brackets, e.g. `::1` or `[::1]`).
     pub fn connect(&mut self, host: &str, port: u16) -> SCResult<GameResult> {
-        let address = format!("{}:{}", host, port);
-        let stream = TcpStream::connect(&address)?;
-        info!("Connected to {}", address);
+        let addresses = Self::resolve(host, port)?;
+        let stream = self.open(&addresses)?;
+        self.configure(&stream)?;
+        info!("Connected to {}", stream.peer_addr()?);
 
         // Begin parsing game messages from the stream.
-        // List all combinations of modes explicitly,
-        // since they generate different generic instantiations
-        // of `run_game`.
-
-        let mode = &self.debug_mode;
-        let game_result = if mode.debug_reader && !mode.debug_writer {
-            self.run(io::stdin(), stream)?
-        } else if !mode.debug_reader && mode.debug_writer {
-            self.run(stream, io::stdout())?
-        } else if mode.debug_reader && mode.debug_writer {
-            self.run(io::stdin(), io::stdout())?
-        } else {
-            self.run(stream.try_clone()?, stream)?
+        self.run_in_mode(stream.try_clone()?, stream, io::stdin(), io::stdout())
+    }
+
+    /// Runs the game on the streams selected by the debug mode, i.e.
+    /// swaps the connection's reader and/or writer for the console's.
+    /// All combinations of modes are listed explicitly, since they
+    /// generate different generic instantiations of `run`.
+    fn run_in_mode(
+        &mut self,
+        stream_read: impl Read,
+        stream_write: impl Write,
+        console_read: impl Read,
+        console_write: impl Write,
+    ) -> SCResult<GameResult> {
+        let mode = self.debug_mode;
+        match (mode.debug_reader, mode.debug_writer) {
+            (true, false) => self.run(console_read, stream_write),
+            (false, true) => self.run(stream_read, console_write),
+            (true, true) => self.run(console_read, console_write),
+            (false, false) => self.run(stream_read, stream_write),
+        }
+    }
+
+    /// Connects to the first reachable address, binding
+    /// to the configured local address if there is one.
+    fn open(&self, addresses: &[SocketAddr]) -> SCResult<TcpStream> {
+        let local = match self.local_address {
+            Some(local) => local,
+            None => return Ok(TcpStream::connect(addresses)?),
         };
+        let mut last_error = None;
+        // A socket can only connect to addresses of the family it is bound to
+        for address in addresses.iter().filter(|a| a.is_ipv4() == local.is_ipv4()) {
+            let socket = Socket::new(
+                Domain::for_address(local),
+                Type::STREAM,
+                Some(Protocol::TCP),
+            )?;
+            socket.bind(&local.into())?;
+            match socket.connect(&(*address).into()) {
+                Ok(()) => return Ok(socket.into()),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(match last_error {
+            Some(e) => e.into(),
+            None => format!("No address of {}'s family to connect to", local).into(),
+        })
+    }
 
-        Ok(game_result)
+    /// Applies the configured socket options to the stream.
+    fn configure(&self, stream: &TcpStream) -> SCResult<()> {
+        stream.set_nodelay(self.nodelay)?;
+        if let Some(time) = self.keepalive {
+            SockRef::from(stream).set_tcp_keepalive(&TcpKeepalive::new().with_time(time))?;
+        }
+        Ok(())
+    }
+
+    /// Resolves the given host and port to socket addresses.
+    fn resolve(host: &str, port: u16) -> SCResult<Vec<SocketAddr>> {
+        let host = host
+            .strip_prefix('[')
+            .and_then(|h| h.strip_suffix(']'))
+            .unwrap_or(host);
+        let addresses: Vec<_> = (host, port).to_socket_addrs()?.collect();
+        if addresses.is_empty() {
+            Err(format!("Could not resolve {}", host).into())
+        } else {
+            Ok(addresses)
+        }
     }
 
     /// Blocks the thread and parses/handles game messages
-    /// from the provided reader.
+    /// from the provided reader. Every debug mode ends up here,
+    /// so the shutdown delay applies uniformly.
     fn run(&mut self, read: impl Read, write: impl Write) -> SCResult<GameResult> {
         let mut buf = Vec::new();
-        let mut reader = Reader::from_reader(BufReader::new(read));
+        let mut read = BufReader::new(read);
+        Self::skip_preamble(&mut read, &self.preamble)?;
+        let mut reader = Reader::from_reader(read);
         let mut writer = Writer::new(BufWriter::new(write));
 
         // Write <protocol>
         writer.write_event(XmlEvent::Start(BytesStart::borrowed_name(b"protocol")))?;
 
         // Send join request
-        let join_xml: Element = match &self.reservation_code {
+        let join = match &self.reservation_code {
             Some(code) => Request::JoinPrepared {
                 reservation_code: code.to_owned(),
             },
             None => Request::Join,
-        }
-        .into();
-        info!("Sending join request {}", &join_xml);
-        join_xml.write_to(&mut writer)?;
+        };
+        info!("Sending join request {}", join);
+        // Flushes the <protocol> start along with the request
+        Self::write_request(&mut writer, join)?;
+        self.write_queued_requests(&mut writer)?;
 
         // Read <protocol>
         loop {
             match reader.read_event(&mut buf)? {
                 XmlEvent::Start(ref start) if start.name() == b"protocol" => {
                     info!("Performed handshake");
+                    self.check_server_version(start)?;
+                    self.delegate.on_connect(self.request_sender());
                     break;
                 }
                 XmlEvent::Text(_) => (),
-                XmlEvent::Eof => return Err(SCError::Eof),
+                XmlEvent::Eof => return Err(SCError::ConnectionClosed),
                 e => warn!("Got unexpected event {:?}", e),
             }
         }
@@ -127,9 +288,16 @@ where
         let mut state: Option<State> = None;
         let mut game_result: Option<GameResult> = None;
         loop {
-            let event_xml = Element::read_from(&mut reader)?;
+            let event_xml = match Element::read_from(&mut reader) {
+                // Servers may close the connection right after the result
+                Err(SCError::ConnectionClosed) if game_result.is_some() => {
+                    info!("Server closed the connection");
+                    break;
+                }
+                result => result?,
+            };
 
-            debug!("Got event {}", event_xml);
+            verbose_debug!("Got event {}", event_xml);
             match Event::try_from(&event_xml) {
                 Ok(Event::Joined { room_id }) => {
                     info!("Joined room {}", room_id);
@@ -140,46 +308,83 @@ where
                         b"sc.protocol.CloseConnection",
                     )))?;
                     writer.write_event(XmlEvent::End(BytesEnd::borrowed(b"protocol")))?;
-                    debug!("Wrote close connection");
+                    // Flush before the shutdown delay rather than when the writer drops
+                    writer.inner().flush()?;
+                    verbose_debug!("Wrote close connection");
                     break;
                 }
                 Ok(Event::Room { room_id, payload }) => {
-                    debug!("Got {} in room {}", payload, room_id);
+                    verbose_debug!("Got {} in room {}", payload, room_id);
                     match payload {
                         EventPayload::Welcome(team) => {
                             self.delegate.on_welcome(team);
                             self.client_team = Some(team);
                         }
                         EventPayload::GameResult(result) => {
-                            self.delegate
-                                .on_game_end(&result, self.client_team.unwrap());
+                            let team = self.client_team.unwrap();
+                            let outcome = result.outcome_for(team);
+                            self.delegate.on_game_end(&result, team, outcome);
                             game_result = Some(result);
                         }
                         EventPayload::Memento(new_state) => {
                             self.delegate.on_update_state(&new_state);
+                            if self.retain_states {
+                                self.states.push(new_state.clone());
+                            }
                             state = Some(new_state);
                         }
+                        EventPayload::Paused(paused) => {
+                            info!("Game {}", if paused { "paused" } else { "unpaused" });
+                            self.delegate.on_pause_changed(paused);
+                        }
                         EventPayload::MoveRequest => {
                             let state = state.as_ref().ok_or_else(|| {
                                 SCError::InvalidState(
                                     "No state available at move request!".to_owned(),
                                 )
                             })?;
-                            let team = state.current_team().ok_or_else(|| {
-                                SCError::InvalidState(
-                                    "No team available at move request!".to_owned(),
-                                )
-                            })?;
-                            let new_move = self.delegate.request_move(state, team);
+                            // The server only ever requests moves from us, so
+                            // prefer our own team over the one derived from parity
+                            let team = match (self.client_team, state.current_team()) {
+                                (Some(own), Some(current)) if own != current => {
+                                    warn!(
+                                        "Move requested for team {}, but state says it's {}'s turn",
+                                        own, current
+                                    );
+                                    own
+                                }
+                                (Some(own), _) => own,
+                                (None, current) => current.ok_or_else(|| {
+                                    SCError::InvalidState(
+                                        "No team available at move request!".to_owned(),
+                                    )
+                                })?,
+                            };
+                            let start = Instant::now();
+                            let new_move =
+                                self.delegate
+                                    .request_move_within(state, team, self.move_budget);
+                            let duration = start.elapsed();
+                            if duration > self.move_budget {
+                                warn!(
+                                    "Choosing a move took {:?}, exceeding the budget of {:?}",
+                                    duration, self.move_budget
+                                );
+                            }
+                            self.delegate.on_move_timing(duration);
+                            self.delegate.on_move_chosen(state, &new_move);
                             let request = Request::Room {
                                 room_id,
                                 payload: RequestPayload::Move(new_move),
                             };
-                            let request_xml = Element::from(request);
-                            request_xml.write_to(&mut writer)?;
+                            Self::write_request(&mut writer, request)?;
                         }
                     };
                 }
+                Ok(Event::Error { message }) => {
+                    error!("Fatal server error: {}", message);
+                    return Err(SCError::FatalServerError(message));
+                }
                 Err(SCError::UnknownElement(element)) => {
                     warn!("Got unknown tag <{}>: {}", element.name(), element);
                 }
@@ -190,9 +395,11 @@ where
                     warn!("Error while parsing event: {:?}", e);
                 }
             }
+
+            self.write_queued_requests(&mut writer)?;
         }
 
-        sleep(Duration::from_secs(2));
+        sleep(self.shutdown_delay);
 
         if let Some(result) = game_result {
             Ok(result)
@@ -203,13 +410,1035 @@ where
         }
     }
 
+    /// Records the version the server announces in its `<protocol>`
+    /// tag (if any) and warns if it doesn't match the expected one.
+    /// Servers don't always announce one, so mismatches often only
+    /// show up as messages that fail to parse.
+    fn check_server_version(&mut self, start: &BytesStart) -> SCResult<()> {
+        let version = Element::try_from(start)?
+            .attribute("version")
+            .ok()
+            .map(|v| v.to_owned());
+        self.server_version = version;
+        if self.has_version_mismatch() {
+            warn!(
+                "Server announced protocol version {}, but this client implements version {}",
+                self.server_version.as_deref().unwrap_or_default(),
+                self.protocol_version
+            );
+        }
+        Ok(())
+    }
+
+    /// Reads the given preamble from the stream, failing if
+    /// the stream starts with anything else.
+    fn skip_preamble(read: &mut impl Read, preamble: &[u8]) -> SCResult<()> {
+        if preamble.is_empty() {
+            return Ok(());
+        }
+        let mut actual = vec![0; preamble.len()];
+        read.read_exact(&mut actual).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => SCError::Eof,
+            _ => e.into(),
+        })?;
+        if actual == preamble {
+            Ok(())
+        } else {
+            Err(SCError::InvalidState(format!(
+                "Expected preamble {:?}, got {:?}",
+                String::from_utf8_lossy(preamble),
+                String::from_utf8_lossy(&actual)
+            )))
+        }
+    }
+
+    /// Writes all requests queued using `send` or a `RequestSender`.
+    fn write_queued_requests<W>(&mut self, writer: &mut Writer<W>) -> SCResult<()>
+    where
+        W: Write,
+    {
+        for request in self.request_receiver.try_iter() {
+            info!("Sending queued request {}", request);
+            Self::write_request(writer, request)?;
+        }
+        Ok(())
+    }
+
+    /// Serializes the given request to the writer and flushes
+    /// it, so the server receives it immediately.
+    fn write_request<W>(writer: &mut Writer<W>, request: Request) -> SCResult<()>
+    where
+        W: Write,
+    {
+        // The 2022 protocol has no message for passing
+        if let Request::Room {
+            payload: RequestPayload::Move(Move::Skip),
+            ..
+        } = request
+        {
+            return Err(SCError::InvalidState(
+                "Cannot send a skip, since the server does not support it".to_owned(),
+            ));
+        }
+        Element::from(request).write_to(writer)
+    }
+
+    /// Queues the given request for sending to the server,
+    /// which happens after the join request or after handling
+    /// the current server message when connected.
+    pub fn send(&mut self, request: Request) {
+        self.request_sender().send(request);
+    }
+
+    /// Queues the given requests for sending in order, see `send`.
+    pub fn send_all(&mut self, requests: impl IntoIterator<Item = Request>) {
+        self.request_sender().send_all(requests);
+    }
+
+    /// Return a handle for sending requests to the server
+    pub fn request_sender(&self) -> RequestSender {
+        RequestSender(self.request_sender.clone())
+    }
+
+    /// The protocol version announced by the server during the
+    /// handshake, if any.
+    pub fn server_version(&self) -> Option<&str> {
+        self.server_version.as_deref()
+    }
+
+    /// Whether the server announced a protocol version whose major
+    /// component differs from the expected one.
+    pub fn has_version_mismatch(&self) -> bool {
+        self.server_version.as_deref().is_some_and(|v| {
+            v.split('.').next().and_then(|major| major.parse().ok()) != Some(self.protocol_version)
+        })
+    }
+
     /// Return team of the client
     pub fn team(&self) -> Option<Team> {
-        self.client_team.clone()
+        self.client_team
     }
 
     /// Return reservation code, if any
     pub fn reservation(&self) -> Option<String> {
         self.reservation_code.clone()
     }
+
+    /// Return the local address the connection is bound to, if configured
+    pub fn local_address(&self) -> Option<SocketAddr> {
+        self.local_address
+    }
+
+    /// Return the time budget for choosing a move
+    pub fn move_budget(&self) -> Duration {
+        self.move_budget
+    }
+
+    /// Return the delay before returning after the game ends
+    pub fn shutdown_delay(&self) -> Duration {
+        self.shutdown_delay
+    }
+
+    /// Return the states received so far, if retained
+    pub fn states(&self) -> &[State] {
+        &self.states
+    }
+
+    /// Return the delegate
+    pub fn delegate(&self) -> &D {
+        &self.delegate
+    }
+}
+
+impl<D> SCClientBuilder<D>
+where
+    D: SCClientDelegate,
+{
+    /// Creates a new client builder with the default
+    /// configuration using the specified delegate.
+    pub fn new(delegate: D) -> Self {
+        Self {
+            delegate,
+            debug_mode: DebugMode::default(),
+            reservation_code: None,
+            shutdown_delay: Duration::from_secs(2),
+            retain_states: false,
+            preamble: Vec::new(),
+            nodelay: true,
+            keepalive: None,
+            local_address: None,
+            protocol_version: PROTOCOL_VERSION,
+            move_budget: DEFAULT_MOVE_BUDGET,
+        }
+    }
+
+    /// Sets the debug mode.
+    pub fn debug_mode(mut self, debug_mode: DebugMode) -> Self {
+        self.debug_mode = debug_mode;
+        self
+    }
+
+    /// Sets the reservation code used to join a prepared game.
+    pub fn reservation(mut self, reservation_code: Option<String>) -> Self {
+        self.reservation_code = reservation_code;
+        self
+    }
+
+    /// Sets the delay to wait for after the game ends
+    /// before returning from `connect`.
+    pub fn shutdown_delay(mut self, shutdown_delay: Duration) -> Self {
+        self.shutdown_delay = shutdown_delay;
+        self
+    }
+
+    /// Sets whether the client should retain every received state,
+    /// which can be inspected using `states` after the game. This is
+    /// off by default to avoid growing memory in long-running processes.
+    pub fn retain_states(mut self, retain_states: bool) -> Self {
+        self.retain_states = retain_states;
+        self
+    }
+
+    /// Sets a fixed preamble that e.g. a proxy prepends to the
+    /// server's stream. It is verified and skipped before the XML
+    /// handshake. By default, the stream is read as raw XML.
+    pub fn preamble(mut self, preamble: impl Into<Vec<u8>>) -> Self {
+        self.preamble = preamble.into();
+        self
+    }
+
+    /// Sets whether Nagle's algorithm should be disabled on the
+    /// connection (`TCP_NODELAY`). This is on by default, since our
+    /// writes are small and latency-sensitive.
+    pub fn nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = nodelay;
+        self
+    }
+
+    /// Enables TCP keep-alive on the connection with the given
+    /// idle time before probes are sent. Off by default.
+    pub fn keepalive(mut self, keepalive: Option<Duration>) -> Self {
+        self.keepalive = keepalive;
+        self
+    }
+
+    /// Sets the local address to bind the connection to, e.g. to
+    /// connect from a specific interface on a multi-homed host. A
+    /// port of 0 lets the OS choose one. By default, the OS picks
+    /// both the interface and the port.
+    pub fn local_address(mut self, local_address: Option<SocketAddr>) -> Self {
+        self.local_address = local_address;
+        self
+    }
+
+    /// Sets the expected major protocol version (i.e. the season),
+    /// see `PROTOCOL_VERSION`. A warning is logged if the server
+    /// announces a different one.
+    pub fn protocol_version(mut self, protocol_version: u32) -> Self {
+        self.protocol_version = protocol_version;
+        self
+    }
+
+    /// Sets the time budget passed to the delegate's
+    /// `request_move_within`, see `DEFAULT_MOVE_BUDGET`.
+    pub fn move_budget(mut self, move_budget: Duration) -> Self {
+        self.move_budget = move_budget;
+        self
+    }
+
+    /// Builds the client.
+    pub fn build(self) -> SCClient<D> {
+        let (request_sender, request_receiver) = channel();
+        SCClient {
+            delegate: self.delegate,
+            debug_mode: self.debug_mode,
+            reservation_code: self.reservation_code,
+            shutdown_delay: self.shutdown_delay,
+            retain_states: self.retain_states,
+            preamble: self.preamble,
+            nodelay: self.nodelay,
+            keepalive: self.keepalive,
+            local_address: self.local_address,
+            protocol_version: self.protocol_version,
+            move_budget: self.move_budget,
+            server_version: None,
+            states: Vec::new(),
+            client_team: None,
+            request_sender,
+            request_receiver,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufWriter, Cursor, Read, Write};
+    use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+    use std::str::FromStr;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use quick_xml::Writer;
+    use socket2::SockRef;
+
+    use crate::game::{Move, State, Team, Vec2};
+    use crate::protocol::{GameResult, MatchOutcome, Request, RequestPayload};
+    use crate::util::{Element, SCError, SCResult};
+
+    use super::{DebugMode, RequestSender, SCClient, SCClientDelegate, DEFAULT_MOVE_BUDGET};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    const WELCOME: &str = r#"<room roomId="abc"><data class="welcomeMessage" color="ONE"/></room>"#;
+    const MEMENTO: &str = r#"
+        <room roomId="abc">
+            <data class="memento">
+                <state turn="0">
+                    <startTeam>ONE</startTeam>
+                    <board>
+                        <pieces>
+                            <entry>
+                                <coordinates x="0" y="0"/>
+                                <piece type="Herzmuschel" team="ONE" count="1"/>
+                            </entry>
+                            <entry>
+                                <coordinates x="7" y="7"/>
+                                <piece type="Herzmuschel" team="TWO" count="1"/>
+                            </entry>
+                        </pieces>
+                    </board>
+                    <ambers></ambers>
+                </state>
+            </data>
+        </room>
+    "#;
+    const MOVE_REQUEST: &str = r#"<room roomId="abc"><data class="moveRequest"/></room>"#;
+    const PAUSED: &str = r#"<room roomId="abc"><data class="paused" paused="true"/></room>"#;
+    const UNPAUSED: &str = r#"<room roomId="abc"><data class="paused" paused="false"/></room>"#;
+    const RESULT: &str = r#"
+        <room roomId="abc">
+            <data class="result">
+                <definition>
+                    <fragment name="Siegpunkte">
+                        <aggregation>SUM</aggregation>
+                        <relevantForRanking>true</relevantForRanking>
+                    </fragment>
+                </definition>
+                <scores>
+                    <entry>
+                        <player name="rad" team="ONE"/>
+                        <score cause="REGULAR" reason=""><part>2</part></score>
+                    </entry>
+                    <entry>
+                        <player name="blues" team="TWO"/>
+                        <score cause="REGULAR" reason=""><part>0</part></score>
+                    </entry>
+                </scores>
+                <winner team="ONE"/>
+            </data>
+        </room>
+    "#;
+
+    /// A delegate that picks the first possible move.
+    struct FirstMoveDelegate;
+
+    impl SCClientDelegate for FirstMoveDelegate {
+        fn request_move(&mut self, state: &State, _my_team: Team) -> Move {
+            state.possible_moves()[0]
+        }
+    }
+
+    /// A delegate that picks the first possible move and
+    /// records the callbacks it receives.
+    #[derive(Default)]
+    struct RecordingDelegate {
+        welcomed: Option<Team>,
+        states: Vec<State>,
+        requests: Vec<Team>,
+        pauses: Vec<bool>,
+        chosen: Vec<(State, Move)>,
+        result: Option<GameResult>,
+        outcome: Option<MatchOutcome>,
+    }
+
+    impl SCClientDelegate for RecordingDelegate {
+        fn on_update_state(&mut self, state: &State) {
+            self.states.push(state.clone());
+        }
+
+        fn on_game_end(&mut self, result: &GameResult, _my_team: Team, outcome: MatchOutcome) {
+            self.result = Some(result.clone());
+            self.outcome = Some(outcome);
+        }
+
+        fn on_welcome(&mut self, team: Team) {
+            self.welcomed = Some(team);
+        }
+
+        fn on_pause_changed(&mut self, paused: bool) {
+            self.pauses.push(paused);
+        }
+
+        fn request_move(&mut self, state: &State, my_team: Team) -> Move {
+            self.requests.push(my_team);
+            state.possible_moves()[0]
+        }
+
+        fn on_move_chosen(&mut self, state: &State, chosen: &Move) {
+            self.chosen.push((state.clone(), *chosen));
+        }
+    }
+
+    /// A delegate that records the time budgets it is given.
+    #[derive(Default)]
+    struct BudgetDelegate {
+        budgets: Vec<Duration>,
+    }
+
+    impl SCClientDelegate for BudgetDelegate {
+        fn request_move(&mut self, _state: &State, _my_team: Team) -> Move {
+            unreachable!("request_move_within is overridden")
+        }
+
+        fn request_move_within(&mut self, state: &State, _my_team: Team, budget: Duration) -> Move {
+            self.budgets.push(budget);
+            state.possible_moves()[0]
+        }
+    }
+
+    /// A delegate that takes a while to pick a move
+    /// and records how long it took.
+    #[derive(Default)]
+    struct SlowDelegate {
+        timings: Vec<Duration>,
+    }
+
+    impl SCClientDelegate for SlowDelegate {
+        fn request_move(&mut self, state: &State, _my_team: Team) -> Move {
+            thread::sleep(Duration::from_millis(20));
+            state.possible_moves()[0]
+        }
+
+        fn on_move_timing(&mut self, duration: Duration) {
+            self.timings.push(duration);
+        }
+    }
+
+    /// A delegate that sends a step request when welcomed.
+    #[derive(Default)]
+    struct SteppingDelegate {
+        sender: Option<RequestSender>,
+    }
+
+    impl SCClientDelegate for SteppingDelegate {
+        fn on_connect(&mut self, sender: RequestSender) {
+            self.sender = Some(sender);
+        }
+
+        fn on_welcome(&mut self, _team: Team) {
+            self.sender.as_ref().unwrap().send(Request::Step {
+                room_id: "abc".to_owned(),
+            });
+        }
+
+        fn request_move(&mut self, state: &State, _my_team: Team) -> Move {
+            state.possible_moves()[0]
+        }
+    }
+
+    /// Wraps the given room messages into a full game as
+    /// sent by the server, from the handshake to leaving the room.
+    fn script(messages: &[&str]) -> String {
+        format!(
+            r#"<protocol><joined roomId="abc"/>{}<left roomId="abc"/>"#,
+            messages.concat()
+        )
+    }
+
+    /// Runs the client against a scripted server on the loopback
+    /// interface that sends the given XML and returns everything
+    /// the client wrote until it disconnected.
+    fn run_scripted<D>(client: &mut SCClient<D>, script: String) -> (SCResult<GameResult>, String)
+    where
+        D: SCClientDelegate,
+    {
+        run_scripted_on(client, script, "127.0.0.1:0", "127.0.0.1")
+    }
+
+    /// Like `run_scripted`, but binds the server to the given address
+    /// and lets the client connect to the given host.
+    fn run_scripted_on<D>(
+        client: &mut SCClient<D>,
+        script: String,
+        bind: &str,
+        host: &str,
+    ) -> (SCResult<GameResult>, String)
+    where
+        D: SCClientDelegate,
+    {
+        let listener = TcpListener::bind(bind).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+            stream.set_write_timeout(Some(TIMEOUT)).unwrap();
+            stream.write_all(script.as_bytes()).unwrap();
+            // Closing our half makes the client hit EOF rather than
+            // block forever if it expects more than the script provides
+            stream.shutdown(Shutdown::Write).unwrap();
+            let mut output = String::new();
+            stream.read_to_string(&mut output).unwrap();
+            output
+        });
+
+        let result = client.connect(host, port);
+        (result, server.join().unwrap())
+    }
+
+    #[test]
+    fn test_builder() {
+        let client = SCClient::builder(FirstMoveDelegate)
+            .debug_mode(DebugMode {
+                debug_reader: false,
+                debug_writer: true,
+            })
+            .reservation(Some("abc".to_owned()))
+            .shutdown_delay(Duration::ZERO)
+            .build();
+
+        assert!(client.debug_mode.debug_writer);
+        assert_eq!(client.reservation(), Some("abc".to_owned()));
+        assert_eq!(client.shutdown_delay(), Duration::ZERO);
+        assert_eq!(client.team(), None);
+    }
+
+    #[test]
+    fn test_scripted_game() {
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        let (result, output) = run_scripted(
+            &mut client,
+            script(&[WELCOME, MEMENTO, MOVE_REQUEST, RESULT]),
+        );
+
+        let result = result.unwrap();
+        assert_eq!(result.winner_team(), Some(Team::One));
+        assert_eq!(client.team(), Some(Team::One));
+
+        let delegate = client.delegate();
+        assert_eq!(delegate.welcomed, Some(Team::One));
+        assert_eq!(delegate.states.len(), 1);
+        assert_eq!(delegate.requests, vec![Team::One]);
+        assert_eq!(delegate.result.as_ref(), Some(&result));
+        assert_eq!(delegate.outcome, Some(MatchOutcome::Win));
+
+        let sent_move = delegate.states[0].possible_moves()[0];
+        assert_eq!(sent_move, Move::new(Vec2::new(0, 0), Vec2::new(1, 1)));
+        let output = Element::from_str(&output).unwrap();
+        let names: Vec<_> = output.childs().iter().map(|c| c.name()).collect();
+        assert_eq!(names, ["join", "room", "sc.protocol.CloseConnection"]);
+        let sent_data = output.child_by_name("room").unwrap().child_by_name("data");
+        assert_eq!(Move::try_from(sent_data.unwrap()).unwrap(), sent_move);
+    }
+
+    #[test]
+    fn test_drawn_game() {
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        let draw = RESULT.replace(r#"<winner team="ONE"/>"#, "");
+        let (result, _) = run_scripted(&mut client, script(&[WELCOME, &draw]));
+
+        assert!(result.unwrap().is_draw());
+        assert_eq!(client.delegate().outcome, Some(MatchOutcome::Draw));
+    }
+
+    #[test]
+    fn test_skip_not_sent() {
+        struct SkippingDelegate;
+
+        impl SCClientDelegate for SkippingDelegate {
+            fn request_move(&mut self, _state: &State, _my_team: Team) -> Move {
+                Move::Skip
+            }
+        }
+
+        let mut client = SCClient::builder(SkippingDelegate)
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        let (result, output) = run_scripted(
+            &mut client,
+            script(&[WELCOME, MEMENTO, MOVE_REQUEST, RESULT]),
+        );
+
+        assert!(matches!(result, Err(SCError::InvalidState(_))));
+        assert!(!output.contains("skip"));
+    }
+
+    #[test]
+    fn test_send() {
+        let mut client = SCClient::builder(SteppingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        client.send(Request::Observe {
+            room_id: "abc".to_owned(),
+        });
+        let (_, output) = run_scripted(&mut client, script(&[WELCOME, RESULT]));
+
+        let output = Element::from_str(&output).unwrap();
+        let names: Vec<_> = output.childs().iter().map(|c| c.name()).collect();
+        assert_eq!(
+            names,
+            ["join", "observe", "step", "sc.protocol.CloseConnection"]
+        );
+    }
+
+    #[test]
+    fn test_debug_modes() {
+        // The console sends a different welcome to tell the readers apart
+        let console_welcome = WELCOME.replace(r#"color="ONE""#, r#"color="TWO""#);
+        for debug_reader in [false, true] {
+            for debug_writer in [false, true] {
+                let mut client = SCClient::builder(RecordingDelegate::default())
+                    .debug_mode(DebugMode {
+                        debug_reader,
+                        debug_writer,
+                    })
+                    .shutdown_delay(Duration::ZERO)
+                    .build();
+                let (mut stream_output, mut console_output) = (Vec::new(), Vec::new());
+                client
+                    .run_in_mode(
+                        Cursor::new(script(&[WELCOME, RESULT])),
+                        &mut stream_output,
+                        Cursor::new(script(&[&console_welcome, RESULT])),
+                        &mut console_output,
+                    )
+                    .unwrap();
+
+                let expected_team = if debug_reader { Team::Two } else { Team::One };
+                assert_eq!(client.team(), Some(expected_team));
+                let (written, untouched) = if debug_writer {
+                    (console_output, stream_output)
+                } else {
+                    (stream_output, console_output)
+                };
+                assert!(String::from_utf8(written).unwrap().contains("<join"));
+                assert!(untouched.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_shutdown_delay() {
+        let short_delay = Duration::from_millis(200);
+        for delay in [short_delay, Duration::ZERO] {
+            let mut client = SCClient::builder(RecordingDelegate::default())
+                .shutdown_delay(delay)
+                .build();
+            let start = Instant::now();
+            client
+                .run(Cursor::new(script(&[WELCOME, RESULT])), Vec::new())
+                .unwrap();
+            let elapsed = start.elapsed();
+
+            assert!(elapsed >= delay);
+            if delay.is_zero() {
+                assert!(elapsed < short_delay);
+            }
+        }
+    }
+
+    #[test]
+    fn test_move_request_prefers_own_team() {
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        // By parity it would be team two's turn
+        let memento = MEMENTO.replace("<startTeam>ONE", "<startTeam>TWO");
+        let (result, _) = run_scripted(
+            &mut client,
+            script(&[WELCOME, &memento, MOVE_REQUEST, RESULT]),
+        );
+
+        result.unwrap();
+        assert_eq!(client.delegate().states[0].current_team(), Some(Team::Two));
+        assert_eq!(client.delegate().requests, vec![Team::One]);
+    }
+
+    #[test]
+    fn test_retain_states() {
+        let second_memento = MEMENTO.replace(r#"turn="0""#, r#"turn="1""#);
+        let messages = [WELCOME, MEMENTO, &second_memento, RESULT];
+
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        run_scripted(&mut client, script(&messages)).0.unwrap();
+        assert!(client.states().is_empty());
+
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .retain_states(true)
+            .build();
+        run_scripted(&mut client, script(&messages)).0.unwrap();
+        let turns: Vec<_> = client.states().iter().map(|s| s.turn()).collect();
+        assert_eq!(turns, [0, 1]);
+        assert_eq!(client.states(), client.delegate().states);
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        let (result, _) = run_scripted(
+            &mut client,
+            script(&[WELCOME, MEMENTO, PAUSED, UNPAUSED, MOVE_REQUEST, RESULT]),
+        );
+
+        result.unwrap();
+        assert_eq!(client.delegate().pauses, [true, false]);
+        assert_eq!(client.delegate().requests, [Team::One]);
+    }
+
+    #[test]
+    fn test_on_move_chosen() {
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        let (result, output) = run_scripted(
+            &mut client,
+            script(&[WELCOME, MEMENTO, MOVE_REQUEST, RESULT]),
+        );
+
+        result.unwrap();
+        let delegate = client.delegate();
+        let chosen = Move::new(Vec2::new(0, 0), Vec2::new(1, 1));
+        assert_eq!(delegate.chosen, [(delegate.states[0].clone(), chosen)]);
+
+        let output = Element::from_str(&output).unwrap();
+        let sent_data = output.child_by_name("room").unwrap().child_by_name("data");
+        assert_eq!(Move::try_from(sent_data.unwrap()).unwrap(), chosen);
+    }
+
+    #[test]
+    fn test_move_budget() {
+        let mut client = SCClient::builder(BudgetDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .move_budget(Duration::from_millis(500))
+            .build();
+        let (result, _) = run_scripted(
+            &mut client,
+            script(&[WELCOME, MEMENTO, MOVE_REQUEST, RESULT]),
+        );
+
+        result.unwrap();
+        assert_eq!(client.move_budget(), Duration::from_millis(500));
+        assert_eq!(client.delegate().budgets, [Duration::from_millis(500)]);
+
+        // Delegates only implementing `request_move` still work
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        let (result, _) = run_scripted(
+            &mut client,
+            script(&[WELCOME, MEMENTO, MOVE_REQUEST, RESULT]),
+        );
+
+        result.unwrap();
+        assert_eq!(client.move_budget(), DEFAULT_MOVE_BUDGET);
+        assert_eq!(client.delegate().requests, [Team::One]);
+    }
+
+    // Only Linux routes all of 127.0.0.0/8 to the loopback interface
+    // without further setup, other platforms fail to bind 127.0.0.2
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_local_address() {
+        // The server can tell the configured source from the default one
+        let local = SocketAddr::from(([127, 0, 0, 2], 0));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, peer) = listener.accept().unwrap();
+            stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+            stream
+                .write_all(script(&[WELCOME, RESULT]).as_bytes())
+                .unwrap();
+            stream.shutdown(Shutdown::Write).unwrap();
+            stream.read_to_end(&mut Vec::new()).unwrap();
+            peer
+        });
+
+        let mut client = SCClient::builder(FirstMoveDelegate)
+            .shutdown_delay(Duration::ZERO)
+            .local_address(Some(local))
+            .build();
+        assert_eq!(client.local_address(), Some(local));
+        client.connect("127.0.0.1", port).unwrap();
+        assert_eq!(server.join().unwrap().ip(), local.ip());
+
+        // An address of the wrong family cannot be connected from
+        let mut client = SCClient::builder(FirstMoveDelegate)
+            .local_address(Some(SocketAddr::from(([0u16; 8], 0))))
+            .build();
+        assert!(client.connect("127.0.0.1", port).is_err());
+    }
+
+    #[test]
+    fn test_move_timing() {
+        let mut client = SCClient::builder(SlowDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        let (result, _) = run_scripted(
+            &mut client,
+            script(&[WELCOME, MEMENTO, MOVE_REQUEST, RESULT]),
+        );
+
+        result.unwrap();
+        let timings = &client.delegate().timings;
+        assert_eq!(timings.len(), 1);
+        assert!(timings[0] >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let v6: SocketAddr = "[::1]:13050".parse().unwrap();
+        assert_eq!(
+            SCClient::<FirstMoveDelegate>::resolve("::1", 13050).unwrap(),
+            [v6]
+        );
+        assert_eq!(
+            SCClient::<FirstMoveDelegate>::resolve("[::1]", 13050).unwrap(),
+            [v6]
+        );
+
+        let v4: SocketAddr = "127.0.0.1:13050".parse().unwrap();
+        assert_eq!(
+            SCClient::<FirstMoveDelegate>::resolve("127.0.0.1", 13050).unwrap(),
+            [v4]
+        );
+        assert!(SCClient::<FirstMoveDelegate>::resolve("localhost", 13050)
+            .unwrap()
+            .iter()
+            .all(|a| a.ip().is_loopback()));
+    }
+
+    #[test]
+    fn test_connect_ipv6() {
+        if TcpListener::bind("[::1]:0").is_err() {
+            // IPv6 is not available in every (containerized) environment
+            return;
+        }
+        for host in ["::1", "[::1]"] {
+            let mut client = SCClient::builder(RecordingDelegate::default())
+                .shutdown_delay(Duration::ZERO)
+                .build();
+            let (result, _) =
+                run_scripted_on(&mut client, script(&[WELCOME, RESULT]), "[::1]:0", host);
+
+            result.unwrap();
+            assert_eq!(client.team(), Some(Team::One));
+        }
+    }
+
+    #[test]
+    fn test_preamble() {
+        let framed = format!("SCPROXY/1\n{}", script(&[WELCOME, RESULT]));
+
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .preamble("SCPROXY/1\n")
+            .build();
+        client.run(Cursor::new(framed.clone()), Vec::new()).unwrap();
+        assert_eq!(client.delegate().welcomed, Some(Team::One));
+
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .preamble("SCPROXY/1\n")
+            .build();
+        assert!(matches!(
+            client.run(Cursor::new("SCPROXY"), Vec::new()),
+            Err(SCError::Eof)
+        ));
+
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .preamble("SCPROXY/2\n")
+            .build();
+        let raw = script(&[WELCOME, RESULT]);
+        assert!(client.run(Cursor::new(raw), Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_socket_options() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let client = SCClient::builder(FirstMoveDelegate).build();
+        let stream = TcpStream::connect(address).unwrap();
+        client.configure(&stream).unwrap();
+        assert!(stream.nodelay().unwrap());
+        assert!(!SockRef::from(&stream).keepalive().unwrap());
+
+        let client = SCClient::builder(FirstMoveDelegate)
+            .nodelay(false)
+            .keepalive(Some(Duration::from_secs(30)))
+            .build();
+        let stream = TcpStream::connect(address).unwrap();
+        client.configure(&stream).unwrap();
+        assert!(!stream.nodelay().unwrap());
+        assert!(SockRef::from(&stream).keepalive().unwrap());
+    }
+
+    #[test]
+    fn test_connection_closed() {
+        // A clean close after the result ends the game normally
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        let closed = format!("<protocol>{}{}</protocol>", WELCOME, RESULT);
+        assert!(client.run(Cursor::new(closed), Vec::new()).is_ok());
+        let closed = format!("<protocol>{}{}", WELCOME, RESULT);
+        assert!(client.run(Cursor::new(closed), Vec::new()).is_ok());
+
+        // ...but not before it
+        let closed = format!("<protocol>{}</protocol>", WELCOME);
+        assert!(matches!(
+            client.run(Cursor::new(closed), Vec::new()),
+            Err(SCError::ConnectionClosed)
+        ));
+        assert!(matches!(
+            client.run(Cursor::new(""), Vec::new()),
+            Err(SCError::ConnectionClosed)
+        ));
+
+        let truncated = format!(r#"<protocol>{}<room roomId="abc">"#, WELCOME);
+        assert!(matches!(
+            client.run(Cursor::new(truncated), Vec::new()),
+            Err(SCError::Eof)
+        ));
+    }
+
+    /// A sink that records how much was written at every flush.
+    #[derive(Default)]
+    struct FlushRecorder {
+        data: Vec<u8>,
+        flushed_lens: Vec<usize>,
+    }
+
+    impl Write for FlushRecorder {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.flushed_lens.push(self.data.len());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_send_all_flushes_each_request() {
+        let moves = [
+            Move::new(Vec2::new(0, 0), Vec2::new(1, 1)),
+            Move::new(Vec2::new(1, 1), Vec2::new(2, 2)),
+            Move::new(Vec2::new(2, 2), Vec2::new(3, 3)),
+        ];
+        let mut client = SCClient::builder(FirstMoveDelegate).build();
+        client.send_all(moves.iter().map(|&m| Request::Room {
+            room_id: "abc".to_owned(),
+            payload: RequestPayload::Move(m),
+        }));
+
+        let mut writer = Writer::new(BufWriter::new(FlushRecorder::default()));
+        client.write_queued_requests(&mut writer).unwrap();
+
+        // Every request reached the underlying stream on its own
+        let sink = writer.inner().get_ref();
+        assert_eq!(sink.flushed_lens.len(), moves.len());
+        let mut start = 0;
+        for (&end, &m) in sink.flushed_lens.iter().zip(&moves) {
+            let xml = std::str::from_utf8(&sink.data[start..end]).unwrap();
+            let element = Element::from_str(xml).unwrap();
+            assert_eq!(
+                Move::try_from(element.child_by_name("data").unwrap()).unwrap(),
+                m
+            );
+            start = end;
+        }
+    }
+
+    #[test]
+    fn test_everything_flushed_before_shutdown() {
+        let mut sink = FlushRecorder::default();
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        client
+            .run(
+                Cursor::new(script(&[WELCOME, MEMENTO, MOVE_REQUEST, RESULT])),
+                &mut sink,
+            )
+            .unwrap();
+
+        // Join, move and close were each flushed as soon as they were written
+        assert_eq!(sink.flushed_lens.len(), 3);
+        assert_eq!(sink.flushed_lens.last(), Some(&sink.data.len()));
+        assert!(String::from_utf8(sink.data)
+            .unwrap()
+            .ends_with("</protocol>"));
+    }
+
+    #[test]
+    fn test_fatal_error_packet() {
+        let mut client = SCClient::builder(RecordingDelegate::default())
+            .shutdown_delay(Duration::ZERO)
+            .build();
+        let packet = r#"<sc.protocol.ErrorPacket message="Unknown reservation code"/>"#;
+        let result = client.run(
+            Cursor::new(script(&[WELCOME, packet, MEMENTO, RESULT])),
+            Vec::new(),
+        );
+
+        match result {
+            Err(SCError::FatalServerError(message)) => {
+                assert_eq!(message, "Unknown reservation code")
+            }
+            r => panic!("Expected fatal error, got {:?}", r),
+        }
+        assert!(client.delegate().states.is_empty());
+    }
+
+    #[test]
+    fn test_protocol_version() {
+        let run = |version: &str, expected: Option<u32>| {
+            let mut builder =
+                SCClient::builder(RecordingDelegate::default()).shutdown_delay(Duration::ZERO);
+            if let Some(expected) = expected {
+                builder = builder.protocol_version(expected);
+            }
+            let mut client = builder.build();
+            let script = script(&[WELCOME, RESULT]).replacen("<protocol>", version, 1);
+            client.run(Cursor::new(script), Vec::new()).unwrap();
+            client
+        };
+
+        let client = run("<protocol>", None);
+        assert_eq!(client.server_version(), None);
+        assert!(!client.has_version_mismatch());
+
+        let client = run(r#"<protocol version="22.1.0">"#, None);
+        assert_eq!(client.server_version(), Some("22.1.0"));
+        assert!(!client.has_version_mismatch());
+
+        let client = run(r#"<protocol version="23.0.2">"#, None);
+        assert!(client.has_version_mismatch());
+
+        let client = run(r#"<protocol version="23.0.2">"#, Some(23));
+        assert!(!client.has_version_mismatch());
+    }
 }
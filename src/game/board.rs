@@ -1,11 +1,18 @@
-use std::{collections::HashMap, ops::Index};
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::{Index, Range},
+};
 
 use crate::util::{Element, SCError, SCResult};
 
-use super::{Move, Piece, Team, Vec2};
+use super::{Move, Piece, SquareChange, Team, Vec2};
 
 pub const BOARD_SIZE: usize = 8;
 
+/// The range of valid x- and y-coordinates on the board.
+pub const BOARD_RANGE: Range<i32> = 0..BOARD_SIZE as i32;
+
 /// An 8x8 game board storing the pieces (8 pieces per team).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Board {
@@ -28,6 +35,38 @@ impl Board {
         }
     }
 
+    /// Parses a board from an ASCII diagram with one line per
+    /// y-coordinate and one character per x-coordinate, as produced
+    /// by the `Display` implementation. Pieces are given by `Piece::char`
+    /// and empty squares by `.`. Surrounding whitespace and blank
+    /// lines are ignored, so diagrams can be indented in tests.
+    pub fn from_ascii(ascii: &str) -> SCResult<Self> {
+        let lines: Vec<_> = ascii
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+        if lines.len() != BOARD_SIZE {
+            return Err(format!("Expected {} lines, got {}", BOARD_SIZE, lines.len()).into());
+        }
+
+        let mut pieces = HashMap::new();
+        for (y, line) in lines.into_iter().enumerate() {
+            let chars: Vec<_> = line.chars().collect();
+            if chars.len() != BOARD_SIZE {
+                return Err(format!("Expected {} squares in line '{}'", BOARD_SIZE, line).into());
+            }
+            for (x, c) in chars.into_iter().enumerate() {
+                if c != '.' {
+                    let piece = Piece::from_char(c)
+                        .ok_or_else(|| SCError::UnknownVariant(format!("Unknown piece '{}'", c)))?;
+                    pieces.insert(Vec2::new(x as i32, y as i32), piece);
+                }
+            }
+        }
+        Ok(Self { pieces })
+    }
+
     /// The pieces on the board.
     pub fn pieces(&self) -> &HashMap<Vec2, Piece> {
         &self.pieces
@@ -45,7 +84,7 @@ impl Board {
 
     /// Checks whether a position in in-bounds.
     pub fn is_in_bounds(pos: Vec2) -> bool {
-        pos.x >= 0 && pos.x < BOARD_SIZE as i32 && pos.y >= 0 && pos.y < BOARD_SIZE as i32
+        pos.is_on_board()
     }
 
     /// Fetches the x-index of the team's 'home' line.
@@ -55,42 +94,57 @@ impl Board {
 
     // Partially translated from https://github.com/software-challenge/backend/blob/89407e5e2f76801ec8beb8f31412da218f5f70e5/plugin/src/main/kotlin/sc/plugin2022/Board.kt
 
-    /// Applies a move to the board.
-    pub fn perform(&mut self, m: Move) {
-        if let Some(piece) = self.pieces.remove(&m.from()) {
+    /// Applies a move to the board without checking its legality, which
+    /// makes it suitable for search code that only applies generated moves.
+    /// Skips leave the board untouched.
+    ///
+    /// The caller must pass a legal move: Illegal moves are only caught
+    /// by assertions in debug builds and otherwise corrupt the board.
+    /// Moves from an empty square panic in any build.
+    pub fn apply_unchecked(&mut self, m: Move) {
+        let (from, to) = match m {
+            Move::Normal { from, to } => (from, to),
+            Move::Skip => return,
+        };
+        if let Some(piece) = self.pieces.remove(&from) {
             debug_assert!(
-                Board::is_in_bounds(m.to()),
+                Board::is_in_bounds(to),
                 "Move destination {} wasn't in bounds!",
-                m.to()
+                to
             );
             debug_assert!(
-                piece.possible_directions().any(|v| v == m.delta()),
+                piece.possible_directions().any(|v| v == to - from),
                 "Move delta {} isn't in the allowed move for the piece {:?}!",
-                m.delta(),
+                to - from,
                 piece
             );
+            debug_assert!(
+                self.pieces.get(&to).map(|p| p.team()) != Some(piece.team()),
+                "Move destination {} is occupied by the own team!",
+                to
+            );
             let new_piece = self
                 .pieces
-                .get(&m.to())
+                .get(&to)
                 .map(|&p| piece.capture(p))
                 .unwrap_or(piece);
-            self.pieces.insert(m.to(), new_piece);
+            self.pieces.insert(to, new_piece);
         } else {
             panic!("Cannot perform empty move!");
         }
     }
 
+    /// Applies a move to the board. Skips leave the board untouched.
+    #[deprecated(note = "renamed to `apply_unchecked`, which documents its contract")]
+    pub fn perform(&mut self, m: Move) {
+        self.apply_unchecked(m);
+    }
+
     /// Checks whether the piece at the given position should be turned
     /// into an amber and, if so, removes it.
     pub fn check_amber(&mut self, pos: Vec2) -> usize {
-        if let Some(piece) = self.pieces.get_mut(&pos) {
-            let ambers = [
-                piece.is_amber(),
-                piece.piece_type().is_light() && pos.x == Self::start_line(piece.team().opponent()),
-            ]
-            .into_iter()
-            .map(|b| if b { 1 } else { 0 })
-            .sum();
+        if let Some(&piece) = self.pieces.get(&pos) {
+            let ambers = Self::ambers_at(piece, pos);
             if ambers > 0 {
                 self.pieces.remove(&pos);
             }
@@ -100,6 +154,17 @@ impl Board {
         }
     }
 
+    /// Counts the ambers the given piece earns at the given position.
+    pub fn ambers_at(piece: Piece, pos: Vec2) -> usize {
+        [
+            piece.is_amber(),
+            piece.piece_type().is_light() && pos.x == Self::start_line(piece.team().opponent()),
+        ]
+        .into_iter()
+        .filter(|&b| b)
+        .count()
+    }
+
     /// Checks whether the given piece can jump to the destination, not
     /// accounting for whether the move itself is valid.
     pub fn can_move(&self, piece: Piece, dest: Vec2) -> bool {
@@ -111,12 +176,185 @@ impl Board {
         if let Some(piece) = self.get(pos) {
             piece
                 .possible_directions()
-                .filter(|&v| self.can_move(piece, pos + v))
+                .flat_map(|v| self.destinations_along(piece, pos, v))
+                .map(|dest| dest - pos)
                 .collect()
         } else {
             Vec::new()
         }
     }
+
+    /// Fetches the squares the piece can move to from the given position
+    /// in the given direction: The adjacent one for stepping pieces and
+    /// every one up to (and including a capture on) the first occupied
+    /// square for sliding pieces (see `PieceType::is_sliding`).
+    pub fn destinations_along(
+        &self,
+        piece: Piece,
+        pos: Vec2,
+        delta: Vec2,
+    ) -> impl Iterator<Item = Vec2> + '_ {
+        let range = if piece.piece_type().is_sliding() {
+            BOARD_SIZE as i32
+        } else {
+            1
+        };
+        self.destinations_within(piece, pos, delta, range)
+    }
+
+    /// Fetches the squares up to `range` steps away in the given direction
+    /// that the piece can reach before (or by capturing on) the first
+    /// occupied square.
+    fn destinations_within(
+        &self,
+        piece: Piece,
+        pos: Vec2,
+        delta: Vec2,
+        range: i32,
+    ) -> impl Iterator<Item = Vec2> + '_ {
+        let mut blocked = false;
+        (1..=range)
+            .map(move |i| pos + delta * i)
+            .take_while(move |&dest| {
+                let reachable = !blocked && self.can_move(piece, dest);
+                blocked = self.pieces.contains_key(&dest);
+                reachable
+            })
+    }
+
+    /// Fetches the pieces on light or dark squares (see
+    /// `Vec2::is_light`), sorted by position.
+    pub fn pieces_on_color(&self, light: bool) -> Vec<(Vec2, Piece)> {
+        let mut pieces: Vec<_> = self
+            .pieces
+            .iter()
+            .filter(|(pos, _)| pos.is_light() == light)
+            .map(|(&pos, &piece)| (pos, piece))
+            .collect();
+        pieces.sort_unstable_by_key(|&(pos, _)| pos);
+        pieces
+    }
+
+    /// Counts the team's pieces with towers counting as many
+    /// pieces as they are high. This is not the number of squares
+    /// the team occupies, which would count each tower once.
+    pub fn total_piece_count(&self, team: Team) -> usize {
+        self.pieces
+            .values()
+            .filter(|p| p.team() == team)
+            .map(|p| p.count())
+            .sum()
+    }
+
+    /// Fetches the board as seen by the other team, i.e. with mirrored
+    /// positions and swapped teams. Since the teams move in opposite
+    /// directions, this preserves which moves are possible.
+    pub fn swap_teams(&self) -> Board {
+        Board {
+            pieces: self
+                .pieces
+                .iter()
+                .map(|(&pos, &piece)| {
+                    let swapped =
+                        Piece::new(piece.piece_type(), piece.team().opponent(), piece.count());
+                    (pos.mirror(), swapped)
+                })
+                .collect(),
+        }
+    }
+
+    /// Fetches the board rotated by 180 degrees, i.e. with both
+    /// coordinates mirrored and the teams swapped (so that each team
+    /// still moves away from its start line). Since every piece moves
+    /// symmetrically along the y-axis, this preserves legality: a move
+    /// is possible on the rotated board iff the rotated move is possible
+    /// for the other team on the original board.
+    pub fn rotate_180(&self) -> Board {
+        self.swap_teams().reflect_horizontal()
+    }
+
+    /// Fetches the board reflected along its horizontal center line,
+    /// i.e. with mirrored y-coordinates and unchanged teams. This
+    /// preserves legality, since every piece moves symmetrically
+    /// along the y-axis. Mirroring x without swapping the teams
+    /// does not, which is why there is no vertical counterpart.
+    pub fn reflect_horizontal(&self) -> Board {
+        Board {
+            pieces: self
+                .pieces
+                .iter()
+                .map(|(&pos, &piece)| (Vec2::new(pos.x, BOARD_SIZE as i32 - 1 - pos.y), piece))
+                .collect(),
+        }
+    }
+
+    /// Fetches the squares that differ between this and the other
+    /// board, sorted by position.
+    pub fn diff(&self, other: &Board) -> Vec<SquareChange> {
+        let mut changes: Vec<_> = self
+            .pieces
+            .keys()
+            .chain(
+                other
+                    .pieces
+                    .keys()
+                    .filter(|pos| !self.pieces.contains_key(pos)),
+            )
+            .map(|&pos| SquareChange {
+                pos,
+                before: self.get(pos),
+                after: other.get(pos),
+            })
+            .filter(|c| c.before != c.after)
+            .collect();
+        changes.sort_unstable_by_key(|c| c.pos);
+        changes
+    }
+
+    /// Computes a Zobrist hash of the pieces, i.e. the XOR of a
+    /// pseudo-random key per occupied square and piece. The keys are
+    /// fixed, so hashes are stable across runs and can be updated
+    /// incrementally by XORing out/in the changed squares.
+    pub fn zobrist_hash(&self) -> u64 {
+        self.pieces
+            .iter()
+            .map(|(&pos, &piece)| Self::zobrist_key(pos, piece))
+            .fold(0, |hash, key| hash ^ key)
+    }
+
+    /// The Zobrist key for the given piece at the given position.
+    pub fn zobrist_key(pos: Vec2, piece: Piece) -> u64 {
+        let square = (pos.y as u64) * BOARD_SIZE as u64 + pos.x as u64;
+        let seed = (square << 16)
+            | ((piece.piece_type() as u64) << 12)
+            | ((piece.team().index() as u64) << 8)
+            | piece.count() as u64;
+        splitmix64(seed)
+    }
+}
+
+/// Scrambles the given value using the SplitMix64 finalizer.
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Renders the board as an ASCII diagram, see `from_ascii`.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in BOARD_RANGE {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for x in BOARD_RANGE {
+                let c = self.get(Vec2::new(x, y)).map(Piece::char).unwrap_or('.');
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Index<Vec2> for Board {
@@ -130,31 +368,95 @@ impl Index<Vec2> for Board {
 impl TryFrom<&Element> for Board {
     type Error = SCError;
 
+    /// Parses the board from its `<pieces>`, which either contain
+    /// `<entry>`s pairing `<coordinates>` with a `<piece>` (the
+    /// server's layout) or `<piece>`s with `x`/`y` attributes.
     fn try_from(elem: &Element) -> SCResult<Self> {
+        let pieces = elem.child_by_name("pieces")?;
+        let entries = pieces.childs_by_name("entry").map(|e| {
+            let coords = Vec2::try_from(e.child_by_name("coordinates")?)?;
+            let piece = Piece::try_from(e.child_by_name("piece")?)?;
+            Ok((coords, piece))
+        });
+        let positioned = pieces
+            .childs_by_name("piece")
+            .map(|p| Ok((Vec2::try_from(p)?, Piece::try_from(p)?)));
         Ok(Board {
-            pieces: elem
-                .child_by_name("pieces")?
-                .childs_by_name("entry")
-                .map(|e| {
-                    let coords = Vec2::try_from(e.child_by_name("coordinates")?)?;
-                    let piece = Piece::try_from(e.child_by_name("piece")?)?;
-                    Ok((coords, piece))
-                })
-                .collect::<SCResult<_>>()?,
+            pieces: entries.chain(positioned).collect::<SCResult<_>>()?,
         })
     }
 }
 
+impl From<&Board> for Element {
+    fn from(board: &Board) -> Self {
+        let mut pieces: Vec<_> = board.pieces.iter().collect();
+        pieces.sort_unstable_by_key(|&(&pos, _)| pos);
+        Element::new("board")
+            .child(
+                Element::new("pieces").childs(
+                    pieces.into_iter().map(|(pos, &piece)| {
+                        Element::new("entry").child(*pos).child(piece).build()
+                    }),
+                ),
+            )
+            .build()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
+    use proptest::prelude::*;
+
     use crate::{
-        game::{Board, Piece, PieceType, Team, Vec2},
+        game::{
+            strategies::{arb_board, arb_team},
+            Board, Move, Piece, PieceType, SquareChange, State, Team, Vec2, BOARD_SIZE,
+        },
         hashmap,
         util::Element,
     };
 
+    proptest! {
+        #[test]
+        fn test_round_trip(board in arb_board()) {
+            let xml = Element::from(&board).to_string();
+            prop_assert_eq!(Board::try_from(&Element::from_str(&xml).unwrap()).unwrap(), board);
+        }
+
+        #[test]
+        fn test_random_move_keeps_board_valid(
+            board in arb_board(),
+            team in arb_team(),
+            index in any::<prop::sample::Index>(),
+        ) {
+            let state = State::try_from(
+                &Element::new("state")
+                    .attribute("turn", 0)
+                    .child(Element::new("startTeam").content(team.as_ref()))
+                    .child(&board)
+                    .child(Element::new("ambers"))
+                    .build(),
+            )
+            .unwrap();
+            let moves = state.possible_moves();
+            prop_assume!(!moves.is_empty());
+
+            let m = moves[index.index(moves.len())];
+            let total = |b: &Board| b.pieces().values().map(|p| p.count()).sum::<usize>();
+            let mut next = state.clone();
+            next.make_move(m);
+
+            prop_assert!(total(next.board()) <= total(state.board()));
+            prop_assert!(next.board().pieces().len() <= state.board().pieces().len());
+            for (&pos, piece) in next.board().pieces() {
+                prop_assert!(Board::is_in_bounds(pos));
+                prop_assert!((1..3).contains(&piece.count()));
+            }
+        }
+    }
+
     #[test]
     fn test_parsing() {
         assert_eq!(
@@ -184,4 +486,305 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn test_parsing_positioned_pieces() {
+        let board = Board::try_from(
+            &Element::from_str(
+                r#"
+            <board>
+                <pieces>
+                    <piece x="2" y="5" type="Seestern" team="ONE" count="1" />
+                    <piece x="6" y="1" type="Moewe" team="TWO" count="2" />
+                </pieces>
+            </board>
+        "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(board.pieces().len(), 2);
+        assert_eq!(
+            board.get(Vec2::new(2, 5)),
+            Some(Piece::new(PieceType::Seestern, Team::One, 1))
+        );
+        assert_eq!(
+            board.get(Vec2::new(6, 1)),
+            Some(Piece::new(PieceType::Moewe, Team::Two, 2))
+        );
+        assert_eq!(board.get(Vec2::new(5, 2)), None);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_perform_alias() {
+        let m = Move::new(Vec2::new(0, 0), Vec2::new(1, 0));
+        let mut board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 1)
+        ]);
+        let mut expected = board.clone();
+        expected.apply_unchecked(m);
+        board.perform(m);
+
+        assert_eq!(board, expected);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "allowed move")]
+    fn test_apply_unchecked_illegal() {
+        let mut board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 1)
+        ]);
+        board.apply_unchecked(Move::new(Vec2::new(0, 0), Vec2::new(1, 1)));
+    }
+
+    #[test]
+    fn test_swap_teams() {
+        let board = Board::new(hashmap![
+            Vec2::new(0, 2) => Piece::new(PieceType::Moewe, Team::One, 1),
+            Vec2::new(6, 5) => Piece::new(PieceType::Robbe, Team::Two, 2)
+        ]);
+        let swapped = board.swap_teams();
+
+        assert_eq!(
+            swapped,
+            Board::new(hashmap![
+                Vec2::new(7, 2) => Piece::new(PieceType::Moewe, Team::Two, 1),
+                Vec2::new(1, 5) => Piece::new(PieceType::Robbe, Team::One, 2)
+            ])
+        );
+        assert_eq!(swapped.swap_teams(), board);
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 1),
+            Vec2::new(1, 0) => Piece::new(PieceType::Robbe, Team::Two, 1),
+            Vec2::new(5, 5) => Piece::new(PieceType::Seestern, Team::Two, 1)
+        ]);
+        let mut after = before.clone();
+        after.apply_unchecked(Move::new(Vec2::new(0, 0), Vec2::new(1, 0)));
+
+        assert_eq!(before.diff(&before), vec![]);
+        assert_eq!(
+            before.diff(&after),
+            vec![
+                SquareChange {
+                    pos: Vec2::new(0, 0),
+                    before: Some(Piece::new(PieceType::Moewe, Team::One, 1)),
+                    after: None,
+                },
+                SquareChange {
+                    pos: Vec2::new(1, 0),
+                    before: Some(Piece::new(PieceType::Robbe, Team::Two, 1)),
+                    after: Some(Piece::new(PieceType::Moewe, Team::One, 2)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_zobrist_hash() {
+        let board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 1),
+            Vec2::new(1, 0) => Piece::new(PieceType::Robbe, Team::Two, 1)
+        ]);
+        let mut moved = board.clone();
+        moved.apply_unchecked(Move::new(Vec2::new(0, 0), Vec2::new(1, 0)));
+
+        assert_eq!(Board::empty().zobrist_hash(), 0);
+        assert_eq!(board.zobrist_hash(), board.clone().zobrist_hash());
+        assert_ne!(board.zobrist_hash(), moved.zobrist_hash());
+        assert_ne!(board.zobrist_hash(), board.swap_teams().zobrist_hash());
+
+        // Hashes can be updated incrementally
+        let incremental = board.zobrist_hash()
+            ^ Board::zobrist_key(Vec2::new(0, 0), board[Vec2::new(0, 0)])
+            ^ Board::zobrist_key(Vec2::new(1, 0), board[Vec2::new(1, 0)])
+            ^ Board::zobrist_key(Vec2::new(1, 0), moved[Vec2::new(1, 0)]);
+        assert_eq!(incremental, moved.zobrist_hash());
+    }
+
+    #[test]
+    fn test_ascii() {
+        let board = Board::from_ascii(
+            r#"
+            M......h
+            .R......
+            ........
+            ..S..s..
+            ........
+            ......r.
+            H.......
+            .......m
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(board.pieces().len(), 8);
+        assert_eq!(
+            board.get(Vec2::new(1, 1)),
+            Some(Piece::new(PieceType::Robbe, Team::One, 1))
+        );
+        assert_eq!(
+            board.get(Vec2::new(7, 0)),
+            Some(Piece::new(PieceType::Herzmuschel, Team::Two, 1))
+        );
+        assert!(board.to_string().starts_with("M......h\n.R......\n"));
+        assert_eq!(Board::from_ascii(&board.to_string()).unwrap(), board);
+        assert_eq!(
+            Board::from_ascii(&Board::empty().to_string()).unwrap(),
+            Board::empty()
+        );
+
+        assert!(Board::from_ascii("M.......").is_err());
+        assert!(Board::from_ascii(&"Q.......\n".repeat(8)).is_err());
+    }
+
+    #[test]
+    fn test_pieces_on_color() {
+        let board = Board::from_ascii(
+            r#"
+            M......h
+            .R......
+            ........
+            ..S..s..
+            ........
+            ......r.
+            H.......
+            .......m
+        "#,
+        )
+        .unwrap();
+
+        let herzmuschel = Piece::new(PieceType::Herzmuschel, Team::One, 1);
+        assert_eq!(
+            board.pieces_on_color(true),
+            vec![
+                (Vec2::new(0, 0), Piece::new(PieceType::Moewe, Team::One, 1)),
+                (Vec2::new(0, 6), herzmuschel),
+                (Vec2::new(1, 1), Piece::new(PieceType::Robbe, Team::One, 1)),
+                (
+                    Vec2::new(5, 3),
+                    Piece::new(PieceType::Seestern, Team::Two, 1)
+                ),
+                (Vec2::new(7, 7), Piece::new(PieceType::Moewe, Team::Two, 1)),
+            ]
+        );
+        assert_eq!(board.pieces_on_color(false).len(), 3);
+    }
+
+    #[test]
+    fn test_destinations_along() {
+        let board = Board::from_ascii(
+            r#"
+            ........
+            ........
+            ........
+            ...M..m.
+            ........
+            ........
+            ........
+            ........
+        "#,
+        )
+        .unwrap();
+        let moewe = board[Vec2::new(3, 3)];
+        let along = |delta| {
+            board
+                .destinations_along(moewe, Vec2::new(3, 3), delta)
+                .collect::<Vec<_>>()
+        };
+
+        // Stepping pieces only reach the adjacent square on an open line
+        assert_eq!(along(Vec2::new(1, 0)), [Vec2::new(4, 3)]);
+        assert_eq!(along(Vec2::new(0, -1)), [Vec2::new(3, 2)]);
+        assert_eq!(board.possible_destinations_from(Vec2::new(3, 3)).len(), 4);
+
+        let edge = board.destinations_along(moewe, Vec2::new(3, 0), Vec2::new(0, -1));
+        assert_eq!(edge.count(), 0);
+        let own = Piece::new(PieceType::Moewe, Team::Two, 1);
+        let blocked = board.destinations_along(own, Vec2::new(5, 3), Vec2::new(1, 0));
+        assert_eq!(blocked.count(), 0);
+
+        // Sliding stops at the edge and captures the first opponent's piece
+        let slide = |delta| {
+            board
+                .destinations_within(moewe, Vec2::new(3, 3), delta, BOARD_SIZE as i32)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            slide(Vec2::new(0, -1)),
+            [Vec2::new(3, 2), Vec2::new(3, 1), Vec2::new(3, 0)]
+        );
+        assert_eq!(
+            slide(Vec2::new(1, 0)),
+            [Vec2::new(4, 3), Vec2::new(5, 3), Vec2::new(6, 3)]
+        );
+        let slide_own =
+            board.destinations_within(own, Vec2::new(0, 3), Vec2::new(1, 0), BOARD_SIZE as i32);
+        assert_eq!(
+            slide_own.collect::<Vec<_>>(),
+            [Vec2::new(1, 3), Vec2::new(2, 3), Vec2::new(3, 3)]
+        );
+    }
+
+    #[test]
+    fn test_total_piece_count() {
+        let mut board = Board::from_ascii(
+            r#"
+            M......h
+            ........
+            ........
+            ........
+            ........
+            ........
+            ........
+            ........
+        "#,
+        )
+        .unwrap();
+        *board.get_mut(Vec2::new(0, 0)).unwrap() = Piece::new(PieceType::Moewe, Team::One, 2);
+
+        let squares = |team| board.pieces().values().filter(|p| p.team() == team).count();
+        assert_eq!(squares(Team::One), 1);
+        assert_eq!(board.total_piece_count(Team::One), 2);
+        assert_eq!(board.total_piece_count(Team::Two), 1);
+        assert_eq!(Board::empty().total_piece_count(Team::One), 0);
+    }
+
+    #[test]
+    fn test_symmetries() {
+        let board = Board::from_ascii(
+            r#"
+            M......h
+            .R......
+            ........
+            ..S..s..
+            ........
+            ......r.
+            H.......
+            .......m
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(board.rotate_180().rotate_180(), board);
+        assert_eq!(board.reflect_horizontal().reflect_horizontal(), board);
+        assert_eq!(
+            board.rotate_180().to_string(),
+            [
+                "M.......", ".......h", ".R......", "........", "..S..s..", "........", "......r.",
+                "H......m",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            board.reflect_horizontal().get(Vec2::new(1, 6)),
+            Some(Piece::new(PieceType::Robbe, Team::One, 1))
+        );
+    }
 }
@@ -0,0 +1,21 @@
+use super::Team;
+
+/// How a game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameOutcome {
+    /// The given team won.
+    Win(Team),
+    /// Neither team won.
+    Draw,
+}
+
+impl GameOutcome {
+    /// The winning team, if any.
+    #[inline]
+    pub fn winner(self) -> Option<Team> {
+        match self {
+            Self::Win(team) => Some(team),
+            Self::Draw => None,
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt;
+
+/// Why a move is illegal, see `State::explain_illegal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IllegalReason {
+    /// The game is already over.
+    GameOver,
+    /// The team skipped although it could move.
+    SkipNotAllowed,
+    /// The origin or destination is outside the board.
+    OffBoard,
+    /// There is no piece at the origin.
+    NoPiece,
+    /// The piece at the origin belongs to the team not on turn.
+    WrongTeam,
+    /// The piece cannot move by the move's delta.
+    InvalidDirection,
+    /// The destination is occupied by a piece of the same team.
+    Blocked,
+}
+
+impl fmt::Display for IllegalReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GameOver => write!(f, "The game is over"),
+            Self::SkipNotAllowed => write!(f, "Cannot skip while moves are possible"),
+            Self::OffBoard => write!(f, "The move leaves the board"),
+            Self::NoPiece => write!(f, "There is no piece to move"),
+            Self::WrongTeam => write!(f, "The piece belongs to the other team"),
+            Self::InvalidDirection => write!(f, "The piece cannot move in that direction"),
+            Self::Blocked => write!(f, "The destination is occupied by an own piece"),
+        }
+    }
+}
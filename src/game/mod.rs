@@ -1,15 +1,31 @@
 mod board;
+mod game_outcome;
+mod illegal_reason;
 mod r#move;
+mod move_effect;
+mod move_generator;
+mod phase;
 mod piece;
 mod piece_type;
+mod square_change;
 mod state;
+#[cfg(test)]
+pub(crate) mod strategies;
 mod team;
+mod terminal_reason;
 mod vec2;
 
 pub use board::*;
+pub use game_outcome::*;
+pub use illegal_reason::*;
+pub use move_effect::*;
+pub use move_generator::*;
+pub use phase::*;
 pub use piece::*;
 pub use piece_type::*;
 pub use r#move::*;
+pub use square_change::*;
 pub use state::*;
 pub use team::*;
+pub use terminal_reason::*;
 pub use vec2::*;
@@ -2,69 +2,133 @@ use std::fmt;
 
 use crate::util::{Element, SCError, SCResult};
 
-use super::Vec2;
+use super::{State, Vec2};
 
-/// An action in the game.
+/// An action in the game. Moves only hold their origin and
+/// destination, so two moves are equal (and hash equally) exactly
+/// when they move the same square, which legality checks such as
+/// `state.possible_moves().contains(&m)` rely on. Any metadata
+/// added in the future must not take part in the comparison.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Move {
-    from: Vec2,
-    to: Vec2,
+pub enum Move {
+    /// Moves the piece at `from` to `to`.
+    Normal { from: Vec2, to: Vec2 },
+    /// Passes the turn without moving a piece, e.g.
+    /// when a team has no legal move left.
+    Skip,
 }
 
 impl Move {
     #[inline]
     pub fn new(from: Vec2, to: Vec2) -> Self {
-        Self { from, to }
+        Self::Normal { from, to }
     }
 
     #[inline]
-    pub fn from(self) -> Vec2 {
-        self.from
+    pub fn is_skip(self) -> bool {
+        matches!(self, Self::Skip)
     }
 
+    /// The origin of the move, unless it is a skip.
     #[inline]
-    pub fn to(self) -> Vec2 {
-        self.to
+    pub fn from(self) -> Option<Vec2> {
+        match self {
+            Self::Normal { from, .. } => Some(from),
+            Self::Skip => None,
+        }
     }
 
+    /// The destination of the move, unless it is a skip.
     #[inline]
-    pub fn delta(self) -> Vec2 {
-        self.to - self.from
+    pub fn to(self) -> Option<Vec2> {
+        match self {
+            Self::Normal { to, .. } => Some(to),
+            Self::Skip => None,
+        }
+    }
+
+    /// The move as seen by the other team, see `Vec2::mirror`.
+    pub fn mirror(self) -> Self {
+        match self {
+            Self::Normal { from, to } => Self::new(from.mirror(), to.mirror()),
+            Self::Skip => Self::Skip,
+        }
+    }
+
+    /// A cheap static key for ordering moves in a search, where sorting
+    /// ascending puts the most promising moves first: moves earning an
+    /// amber come first, then captures by the value of the captured
+    /// tower (see `PieceType::default_value`), then quiet moves.
+    pub fn ordering_key(&self, state: &State) -> i32 {
+        let effect = state.classify(*self);
+        let amber = if effect.amber.is_some() { 1000 } else { 0 };
+        let capture = effect
+            .captured
+            .map(|p| p.piece_type().default_value() * p.count() as i32)
+            .unwrap_or(0);
+        -(amber + capture)
+    }
+
+    /// The vector from origin to destination, unless it is a skip.
+    #[inline]
+    pub fn delta(self) -> Option<Vec2> {
+        match self {
+            Self::Normal { from, to } => Some(to - from),
+            Self::Skip => None,
+        }
     }
 }
 
 impl fmt::Display for Move {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} -> {}", self.from, self.to)
+        match self {
+            Self::Normal { from, to } => write!(f, "{} -> {}", from, to),
+            Self::Skip => write!(f, "Skip"),
+        }
     }
 }
 
 impl TryFrom<&Element> for Move {
     type Error = SCError;
 
+    /// Parses a move from its `<from>` and `<to>` children,
+    /// or a skip if both are missing. The coordinates are read
+    /// either from the children's `x`/`y` attributes or from a
+    /// nested `<coordinates>` element.
     fn try_from(element: &Element) -> SCResult<Self> {
-        Ok(Move {
-            from: element.child_by_name("from")?.try_into()?,
-            to: element.child_by_name("to")?.try_into()?,
+        let from = element.child_by_name("from");
+        let to = element.child_by_name("to");
+        if from.is_err() && to.is_err() {
+            return Ok(Move::Skip);
+        }
+        Ok(Move::Normal {
+            from: parse_position(from?)?,
+            to: parse_position(to?)?,
         })
     }
 }
 
+/// Parses a `<from>`/`<to>` element in either layout.
+fn parse_position(element: &Element) -> SCResult<Vec2> {
+    match element.child_by_name("coordinates") {
+        Ok(coordinates) if element.attribute("x").is_err() => coordinates.try_into(),
+        _ => element.try_into(),
+    }
+}
+
 impl From<Move> for Element {
+    /// Serializes the move as a `<data>` payload. Note that the 2022
+    /// protocol has no message for skips, so the client refuses to
+    /// send the `<data class="skip"/>` produced for them.
     fn from(m: Move) -> Self {
-        Element::new("data")
-            .attribute("class", "move")
-            .child(
-                Element::new("from")
-                    .attribute("x", m.from.x)
-                    .attribute("y", m.from.y),
-            )
-            .child(
-                Element::new("to")
-                    .attribute("x", m.to.x)
-                    .attribute("y", m.to.y),
-            )
-            .build()
+        match m {
+            Move::Normal { from, to } => Element::new("data")
+                .attribute("class", "move")
+                .child(from.to_element("from"))
+                .child(to.to_element("to"))
+                .build(),
+            Move::Skip => Element::new("data").attribute("class", "skip").build(),
+        }
     }
 }
 
@@ -73,10 +137,53 @@ mod tests {
     use std::str::FromStr;
 
     use crate::{
-        game::{Move, Vec2},
+        game::{Move, State, Team, Vec2},
         util::Element,
     };
 
+    #[test]
+    fn test_ordering_key() {
+        let state = State::try_from(
+            &Element::from_str(
+                r#"
+            <state turn="0">
+                <startTeam>ONE</startTeam>
+                <board>
+                    <pieces>
+                        <entry>
+                            <coordinates x="0" y="0"/>
+                            <piece type="Moewe" team="ONE" count="1"/>
+                        </entry>
+                        <entry>
+                            <coordinates x="1" y="0"/>
+                            <piece type="Herzmuschel" team="TWO" count="1"/>
+                        </entry>
+                        <entry>
+                            <coordinates x="6" y="5"/>
+                            <piece type="Seestern" team="ONE" count="1"/>
+                        </entry>
+                    </pieces>
+                </board>
+                <ambers></ambers>
+            </state>
+        "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let amber = Move::new(Vec2::new(6, 5), Vec2::new(7, 5));
+        let capture = Move::new(Vec2::new(0, 0), Vec2::new(1, 0));
+        let quiet = Move::new(Vec2::new(0, 0), Vec2::new(0, 1));
+
+        assert!(amber.ordering_key(&state) < capture.ordering_key(&state));
+        assert!(capture.ordering_key(&state) < quiet.ordering_key(&state));
+
+        let mut moves = state.possible_moves();
+        moves.sort_by_key(|m| m.ordering_key(&state));
+        assert_eq!(state.classify(moves[0]).amber, Some(Team::One));
+        assert_eq!(moves.last().map(|m| m.ordering_key(&state)), Some(0));
+    }
+
     #[test]
     fn test_parsing() {
         assert_eq!(
@@ -92,10 +199,69 @@ mod tests {
                 .unwrap()
             )
             .unwrap(),
-            Move {
-                from: Vec2::new(3, 4),
-                to: Vec2::new(5, 9),
-            }
+            Move::new(Vec2::new(3, 4), Vec2::new(5, 9))
+        );
+    }
+
+    #[test]
+    fn test_parsing_nested_coordinates() {
+        let nested = Element::from_str(
+            r#"
+            <data class="move">
+                <from><coordinates x="3" y="4" /></from>
+                <to><coordinates x="4" y="4" /></to>
+            </data>
+        "#,
+        )
+        .unwrap();
+        assert_eq!(
+            Move::try_from(&nested).unwrap(),
+            Move::new(Vec2::new(3, 4), Vec2::new(4, 4))
+        );
+
+        let mixed = Element::from_str(
+            r#"<data class="move"><from x="1" y="2" /><to><coordinates x="2" y="2" /></to></data>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Move::try_from(&mixed).unwrap(),
+            Move::new(Vec2::new(1, 2), Vec2::new(2, 2))
+        );
+
+        let empty = Element::from_str(r#"<data class="move"><from /><to /></data>"#).unwrap();
+        assert!(Move::try_from(&empty).is_err());
+    }
+
+    #[test]
+    fn test_parsing_skip() {
+        assert_eq!(
+            Move::try_from(&Element::from_str(r#"<lastMove class="skip" />"#).unwrap()).unwrap(),
+            Move::Skip
         );
+        assert!(Move::try_from(
+            &Element::from_str(r#"<lastMove><from x="1" y="2" /></lastMove>"#).unwrap()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_equality() {
+        let parsed = Move::try_from(
+            &Element::from_str(
+                r#"
+            <data class="move" comment="irrelevant">
+                <from x="3" y="4" />
+                <to x="5" y="9" />
+            </data>
+        "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(parsed, Move::new(Vec2::new(3, 4), Vec2::new(5, 9)));
+        assert_ne!(parsed, Move::new(Vec2::new(5, 9), Vec2::new(3, 4)));
+        assert_ne!(parsed, Move::Skip);
+        assert!([Move::Skip, Move::new(Vec2::new(3, 4), Vec2::new(5, 9))].contains(&parsed));
     }
 }
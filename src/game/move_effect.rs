@@ -0,0 +1,22 @@
+use super::{Piece, Team};
+
+/// What applying a move did to the game, e.g. for move ordering.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MoveEffect {
+    /// The opponent piece that was captured, if any.
+    pub captured: Option<Piece>,
+    /// Whether the moved piece ended up in a tower that stays on the board.
+    /// Since pieces cannot move onto their own team's pieces, towers only
+    /// grow by capturing.
+    pub stacked: bool,
+    /// The team that earned at least one amber, if any.
+    pub amber: Option<Team>,
+}
+
+impl MoveEffect {
+    /// Whether the move neither captured nor earned an amber.
+    #[inline]
+    pub fn is_quiet(self) -> bool {
+        self.captured.is_none() && self.amber.is_none()
+    }
+}
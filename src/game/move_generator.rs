@@ -0,0 +1,23 @@
+use super::{Move, State};
+
+/// Generates the legal moves in a state. This allows plugging in
+/// alternative implementations (e.g. bitboard-based ones) using
+/// `State::possible_moves_with`.
+pub trait MoveGenerator {
+    /// Generates the moves the current team can make.
+    fn generate(&self, state: &State) -> Vec<Move>;
+}
+
+/// The straightforward move generator used by `State::possible_moves`,
+/// which tries every direction of every piece of the current team.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultMoveGenerator;
+
+impl MoveGenerator for DefaultMoveGenerator {
+    fn generate(&self, state: &State) -> Vec<Move> {
+        state
+            .current_team()
+            .map(|team| state.possible_moves_for(team))
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,10 @@
+/// A coarse stage of the game, e.g. for switching evaluation functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// The first rounds, while (almost) all pieces are still on the board.
+    Opening,
+    /// Everything between the opening and the endgame.
+    Middlegame,
+    /// The last rounds or a sparsely populated board.
+    Endgame,
+}
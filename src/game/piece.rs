@@ -3,6 +3,13 @@ use crate::util::{Element, SCError, SCResult};
 use super::{PieceType, Team, Vec2};
 
 /// A placeable figure on the board.
+///
+/// A tower only records its topmost type and its height, since under
+/// the 2022 rules the pieces underneath never matter: a tower moves
+/// like its topmost piece, belongs to the capturing team and turns
+/// into an amber once it reaches a height of 3, regardless of which
+/// types were captured. The server's `<piece>` elements likewise only
+/// carry `type`, `team` and `count`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Piece {
     /// Type of the (topmost) piece.
@@ -54,6 +61,27 @@ impl Piece {
         captured
     }
 
+    /// A single-character representation of the piece, i.e. its type's
+    /// abbreviation in uppercase for team one and lowercase for team two.
+    /// The height of towers is not represented.
+    pub fn char(self) -> char {
+        match self.team {
+            Team::One => self.piece_type.char().to_ascii_uppercase(),
+            Team::Two => self.piece_type.char().to_ascii_lowercase(),
+        }
+    }
+
+    /// Parses a single piece (with a count of 1) from its
+    /// character representation, the inverse of `char`.
+    pub fn from_char(c: char) -> Option<Self> {
+        let team = if c.is_ascii_uppercase() {
+            Team::One
+        } else {
+            Team::Two
+        };
+        PieceType::from_char(c).map(|piece_type| Self::new(piece_type, team, 1))
+    }
+
     /// The directions this piece can move in.
     pub fn possible_directions(self) -> impl Iterator<Item = Vec2> {
         let direction = self.team.direction();
@@ -76,15 +104,35 @@ impl TryFrom<&Element> for Piece {
     }
 }
 
+impl From<Piece> for Element {
+    fn from(piece: Piece) -> Self {
+        Element::new("piece")
+            .attribute("type", piece.piece_type)
+            .attribute("team", piece.team)
+            .attribute("count", piece.count)
+            .build()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
+    use proptest::prelude::*;
+
     use crate::{
-        game::{Piece, PieceType, Team},
+        game::{strategies::arb_piece_of, strategies::arb_team, Piece, PieceType, Team},
         util::Element,
     };
 
+    proptest! {
+        #[test]
+        fn test_round_trip(piece in arb_team().prop_flat_map(arb_piece_of)) {
+            let xml = Element::from(piece).to_string();
+            prop_assert_eq!(Piece::try_from(&Element::from_str(&xml).unwrap()).unwrap(), piece);
+        }
+    }
+
     #[test]
     fn test_parsing() {
         assert_eq!(
@@ -104,4 +152,22 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_two_piece_tower() {
+        let robbe = Piece::new(PieceType::Robbe, Team::One, 1);
+        let moewe = Piece::new(PieceType::Moewe, Team::Two, 1);
+        let tower = robbe.capture(moewe);
+
+        assert_eq!(tower, Piece::new(PieceType::Robbe, Team::One, 2));
+        assert!(!tower.is_amber());
+        assert!(tower.possible_directions().eq(robbe.possible_directions()));
+
+        let xml = Element::from(tower).to_string();
+        assert_eq!(xml, r#"<piece count="2" team="ONE" type="Robbe"/>"#);
+        assert_eq!(
+            Piece::try_from(&Element::from_str(&xml).unwrap()).unwrap(),
+            tower
+        );
+    }
 }
@@ -24,6 +24,51 @@ impl PieceType {
         !matches!(self, Self::Robbe)
     }
 
+    /// Checks whether the piece may move multiple squares along its
+    /// directions (until blocked) rather than stepping once. None of
+    /// the 2022 pieces slide: the 'herzmuschel', 'moewe' and 'seestern'
+    /// step to an adjacent square and the 'robbe' jumps like a knight.
+    #[inline]
+    pub fn is_sliding(self) -> bool {
+        match self {
+            Self::Herzmuschel | Self::Moewe | Self::Seestern | Self::Robbe => false,
+        }
+    }
+
+    /// A rough static value of the piece type for evaluation and move
+    /// ordering. The 'robbe' is worth the most since it moves furthest,
+    /// while the 'herzmuschel' only has two possible moves.
+    pub fn default_value(self) -> i32 {
+        match self {
+            Self::Herzmuschel => 1,
+            Self::Moewe => 2,
+            Self::Seestern => 2,
+            Self::Robbe => 3,
+        }
+    }
+
+    /// A single-letter abbreviation of the type, e.g. 'M' for 'Moewe'.
+    pub fn char(self) -> char {
+        match self {
+            Self::Herzmuschel => 'H',
+            Self::Moewe => 'M',
+            Self::Seestern => 'S',
+            Self::Robbe => 'R',
+        }
+    }
+
+    /// Parses a single-letter abbreviation (case-insensitively),
+    /// the inverse of `char`.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'H' => Some(Self::Herzmuschel),
+            'M' => Some(Self::Moewe),
+            'S' => Some(Self::Seestern),
+            'R' => Some(Self::Robbe),
+            _ => None,
+        }
+    }
+
     /// The directions in which this piece is allowed to move.
     pub fn possible_directions(self) -> Vec<Vec2> {
         match self {
@@ -49,16 +94,46 @@ impl fmt::Display for PieceType {
     }
 }
 
+/// Parses a piece type case-insensitively. The 'möwe' is
+/// accepted both as `Moewe` and with an umlaut as `Möwe`.
 impl FromStr for PieceType {
     type Err = SCError;
 
     fn from_str(s: &str) -> SCResult<Self> {
-        match s {
-            "Herzmuschel" => Ok(Self::Herzmuschel),
-            "Moewe" => Ok(Self::Moewe),
-            "Seestern" => Ok(Self::Seestern),
-            "Robbe" => Ok(Self::Robbe),
+        match s.to_lowercase().as_str() {
+            "herzmuschel" => Ok(Self::Herzmuschel),
+            "moewe" | "möwe" => Ok(Self::Moewe),
+            "seestern" => Ok(Self::Seestern),
+            "robbe" => Ok(Self::Robbe),
             _ => Err(SCError::UnknownVariant(format!("Unknown piece type {}", s))),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::game::PieceType;
+
+    #[test]
+    fn test_parsing() {
+        assert_eq!(PieceType::from_str("Moewe").unwrap(), PieceType::Moewe);
+        assert_eq!(PieceType::from_str("Möwe").unwrap(), PieceType::Moewe);
+        assert_eq!(PieceType::from_str("MÖWE").unwrap(), PieceType::Moewe);
+        assert_eq!(PieceType::from_str("robbe").unwrap(), PieceType::Robbe);
+        assert!(PieceType::from_str("Qualle").is_err());
+    }
+
+    #[test]
+    fn test_is_sliding() {
+        for piece_type in [
+            PieceType::Herzmuschel,
+            PieceType::Moewe,
+            PieceType::Seestern,
+            PieceType::Robbe,
+        ] {
+            assert!(!piece_type.is_sliding(), "{} slides", piece_type);
+        }
+    }
+}
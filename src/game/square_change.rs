@@ -0,0 +1,12 @@
+use super::{Piece, Vec2};
+
+/// A change of a single square between two boards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SquareChange {
+    /// The position of the square.
+    pub pos: Vec2,
+    /// The piece on the square before the change.
+    pub before: Option<Piece>,
+    /// The piece on the square after the change.
+    pub after: Option<Piece>,
+}
@@ -1,13 +1,36 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
+
+use rand::{seq::SliceRandom, Rng};
 
 use crate::util::{Element, SCError, SCResult};
 
-use super::{Board, Move, Piece, Team, Vec2};
+use super::{
+    Board, DefaultMoveGenerator, GameOutcome, IllegalReason, Move, MoveEffect, MoveGenerator,
+    Phase, Piece, Team, TerminalReason, Vec2, BOARD_RANGE, BOARD_SIZE,
+};
 
+/// The number of rounds (i.e. pairs of turns) after
+/// which the game ends, i.e. 60 turns in total.
 pub const ROUND_LIMIT: usize = 30;
 
-/// The state of the game at a point in time.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// The last round considered part of the opening.
+pub const OPENING_ROUND_LIMIT: usize = 5;
+/// The minimum number of occupied squares during the opening.
+pub const OPENING_MIN_PIECES: usize = 14;
+/// The number of rounds before `ROUND_LIMIT` that count as endgame.
+pub const ENDGAME_ROUNDS: usize = 5;
+/// The maximum number of occupied squares that counts as endgame.
+pub const ENDGAME_MAX_PIECES: usize = 6;
+
+/// The state of the game at a point in time. Equality (and hashing)
+/// only considers the position, not how it was reached (i.e. neither
+/// the last move nor the tracked history), so states reached through
+/// different move orders compare equal. A team without an amber entry
+/// is treated as having no ambers.
+#[derive(Debug, Clone)]
 pub struct State {
     /// The game board.
     board: Board,
@@ -19,6 +42,11 @@ pub struct State {
     last_move: Option<Move>,
     /// The starting team.
     start_team: Option<Team>,
+    /// The moves performed on this state, if tracked.
+    history: Option<Vec<Move>>,
+    /// The Zobrist hashes of the positions reached (including the
+    /// one history tracking was enabled on), if history is tracked.
+    position_hashes: Option<Vec<u64>>,
 }
 
 impl State {
@@ -28,12 +56,67 @@ impl State {
         &self.board
     }
 
+    /// Fetches a copy of this state with the board replaced, e.g. to
+    /// explore hypothetical positions. All other fields (including
+    /// any tracked history) are kept as they are.
+    pub fn with_board(&self, board: Board) -> State {
+        State {
+            board,
+            ..self.clone()
+        }
+    }
+
+    /// Fetches the state as seen by the given team, i.e. such that
+    /// the team plays as `Team::One` from the left (moving towards
+    /// increasing x). For `Team::Two`, the board, ambers, starting team
+    /// and moves are swapped/mirrored using `Board::swap_teams`, which
+    /// preserves the possible moves. Repetition tracking restarts at
+    /// the flipped position.
+    pub fn from_perspective(&self, team: Team) -> State {
+        if team == Team::One {
+            return self.clone();
+        }
+        let mut flipped = State {
+            board: self.board.swap_teams(),
+            ambers: self
+                .ambers
+                .iter()
+                .map(|(&team, &ambers)| (team.opponent(), ambers))
+                .collect(),
+            turn: self.turn,
+            last_move: self.last_move.map(Move::mirror),
+            start_team: self.start_team.map(Team::opponent),
+            history: self
+                .history
+                .as_ref()
+                .map(|h| h.iter().map(|m| m.mirror()).collect()),
+            position_hashes: None,
+        };
+        if flipped.history.is_some() {
+            flipped.position_hashes = Some(vec![flipped.zobrist_hash()]);
+        }
+        flipped
+    }
+
     /// The ambers per team.
     #[inline]
     pub fn ambers(&self) -> &HashMap<Team, usize> {
         &self.ambers
     }
 
+    /// Credits an amber to the given team, e.g. when
+    /// applying moves in place during a search.
+    #[inline]
+    pub fn add_amber(&mut self, team: Team) {
+        *self.ambers.entry(team).or_insert(0) += 1;
+    }
+
+    /// The ambers of the given team, counting a missing entry as none.
+    #[inline]
+    pub fn amber_count(&self, team: Team) -> usize {
+        self.ambers.get(&team).copied().unwrap_or(0)
+    }
+
     /// The turn of the game.
     #[inline]
     pub fn turn(&self) -> usize {
@@ -43,7 +126,7 @@ impl State {
     /// Fetches the round, i.e. `(turn + 1) / 2`.
     #[inline]
     pub fn round(&self) -> usize {
-        (self.turn + 1) / 2
+        self.turn.div_ceil(2)
     }
 
     /// The most recent move, if available.
@@ -58,14 +141,63 @@ impl State {
         self.start_team
     }
 
+    /// The moves performed on this state, if history
+    /// tracking was enabled using `with_history`.
+    #[inline]
+    pub fn history(&self) -> Option<&[Move]> {
+        self.history.as_deref()
+    }
+
+    /// Enables tracking the moves performed on this state. Tracking
+    /// is off by default to avoid the memory overhead during search.
+    pub fn with_history(mut self) -> Self {
+        if self.history.is_none() {
+            self.history = Some(Vec::new());
+            self.position_hashes = Some(vec![self.zobrist_hash()]);
+        }
+        self
+    }
+
+    /// Computes a Zobrist hash of the position, i.e. the board
+    /// and the team to move (ambers and the turn are ignored).
+    pub fn zobrist_hash(&self) -> u64 {
+        let side = if self.turn.is_multiple_of(2) {
+            0
+        } else {
+            super::board::splitmix64(u64::MAX)
+        };
+        self.board.zobrist_hash() ^ side
+    }
+
+    /// Counts how often the current position has occurred since
+    /// history tracking was enabled (including the current
+    /// occurrence), or 1 if history is not tracked.
+    pub fn repetition_count(&self) -> usize {
+        let hash = self.zobrist_hash();
+        self.position_hashes
+            .as_ref()
+            .map(|hashes| hashes.iter().filter(|&&h| h == hash).count())
+            .unwrap_or(1)
+    }
+
+    /// Checks whether the current position occurred three times.
+    /// The official 2022 rules have no such draw (the game ends after
+    /// the round limit instead), but searches and self-play may use
+    /// this to cut off cycles. Requires history tracking.
+    pub fn is_repetition_draw(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+
     /// The current team, computed from the starting team and the turn.
     pub fn current_team(&self) -> Option<Team> {
-        let start_team = self.start_team?;
-        Some(if self.turn % 2 == 0 {
-            start_team
-        } else {
-            start_team.opponent()
-        })
+        Some(Team::from_turn(self.turn, self.start_team?))
+    }
+
+    /// The team to move after the given move, if the starting team is known.
+    /// The 2022 rules grant no bonus turns (not even for earning an amber),
+    /// so this is always the opponent of the current team.
+    pub fn team_to_move_after(&self, _m: Move) -> Option<Team> {
+        self.current_team().map(Team::opponent)
     }
 
     // Partially translated from https://github.com/software-challenge/backend/blob/89407e5e2f76801ec8beb8f31412da218f5f70e5/plugin/src/main/kotlin/sc/plugin2022/GameState.kt
@@ -80,38 +212,497 @@ impl State {
             .map(|(&pos, &piece)| (pos, piece))
     }
 
-    /// Fetches the possible moves.
+    /// Fetches the possible moves. The moves are sorted by origin,
+    /// then by destination, so the order is deterministic across runs.
     pub fn possible_moves(&self) -> Vec<Move> {
-        self.current_pieces()
-            .flat_map(|(pos, piece)| {
+        self.possible_moves_with(&DefaultMoveGenerator)
+    }
+
+    /// Fetches the possible moves using the given generator.
+    pub fn possible_moves_with<G>(&self, generator: &G) -> Vec<Move>
+    where
+        G: MoveGenerator + ?Sized,
+    {
+        generator.generate(self)
+    }
+
+    /// Fetches the moves the given team could make on this board,
+    /// regardless of whose turn it is, in the same order as `possible_moves`.
+    pub fn possible_moves_for(&self, team: Team) -> Vec<Move> {
+        let mut moves: Vec<_> = self.moves_for(team).collect();
+        moves.sort_unstable_by_key(|m| (m.from(), m.to()));
+        moves
+    }
+
+    /// Counts the possible moves without collecting them.
+    pub fn legal_move_count(&self) -> usize {
+        self.current_team()
+            .map(|team| self.moves_for(team).count())
+            .unwrap_or(0)
+    }
+
+    /// Lazily generates the moves of the given team in arbitrary order.
+    fn moves_for(&self, team: Team) -> impl Iterator<Item = Move> + '_ {
+        self.board
+            .pieces()
+            .iter()
+            .filter(move |&(_, piece)| piece.team() == team)
+            .flat_map(move |(&pos, &piece)| {
                 piece
                     .possible_directions()
-                    .map(move |delta| Move::new(pos, pos + delta))
-                    .filter(move |m| {
-                        Board::is_in_bounds(m.to())
-                            && self.board.get(m.to()).map(|p| p.team()) != Some(piece.team())
+                    .flat_map(move |delta| self.board.destinations_along(piece, pos, delta))
+                    .map(move |to| {
+                        debug_assert!(
+                            Board::is_in_bounds(to),
+                            "Generated out-of-bounds move {} -> {}!",
+                            pos,
+                            to
+                        );
+                        Move::new(pos, to)
                     })
             })
+    }
+
+    /// Fetches the positions of the given team's pieces that the
+    /// opponent could capture with its next move, sorted by position.
+    pub fn threatened_pieces(&self, team: Team) -> Vec<Vec2> {
+        let mut threatened: Vec<_> = self
+            .possible_moves_for(team.opponent())
+            .into_iter()
+            .filter_map(|m| m.to())
+            .filter(|&to| self.board.get(to).is_some_and(|p| p.team() == team))
+            .collect();
+        threatened.sort_unstable();
+        threatened.dedup();
+        threatened
+    }
+
+    /// Fetches the possible moves grouped by their origin.
+    pub fn moves_by_origin(&self) -> HashMap<Vec2, Vec<Move>> {
+        let mut moves = HashMap::<Vec2, Vec<Move>>::new();
+        for m in self.possible_moves() {
+            if let Some(from) = m.from() {
+                moves.entry(from).or_default().push(m);
+            }
+        }
+        moves
+    }
+
+    /// Checks whether the position is quiet, i.e. no legal
+    /// move captures a piece or earns an amber.
+    pub fn is_quiet(&self) -> bool {
+        self.capture_moves().is_empty()
+    }
+
+    /// Fetches the possible moves that capture a piece
+    /// or earn an amber, in the same order as `possible_moves`.
+    pub fn capture_moves(&self) -> Vec<Move> {
+        self.possible_moves()
+            .into_iter()
+            .filter(|&m| !self.classify(m).is_quiet())
             .collect()
     }
 
+    /// Counts the occupied squares, i.e. the pieces of both teams,
+    /// where a tower counts as a single piece.
+    pub fn piece_count_total(&self) -> usize {
+        self.board.pieces().len()
+    }
+
+    /// Computes the material of the given team minus that of its
+    /// opponent, where each tower is worth its type's default value
+    /// (see `PieceType::default_value`) times its height.
+    pub fn material_balance(&self, team: Team) -> i32 {
+        self.board
+            .pieces()
+            .values()
+            .map(|piece| {
+                let value = piece.piece_type().default_value() * piece.count() as i32;
+                if piece.team() == team {
+                    value
+                } else {
+                    -value
+                }
+            })
+            .sum()
+    }
+
+    /// Classifies the stage of the game. The game is in the opening
+    /// during the first `OPENING_ROUND_LIMIT` rounds as long as at least
+    /// `OPENING_MIN_PIECES` of the initial 16 pieces are still on the
+    /// board. It is in the endgame during the last `ENDGAME_ROUNDS`
+    /// rounds or once at most `ENDGAME_MAX_PIECES` pieces are left.
+    pub fn game_phase(&self) -> Phase {
+        let pieces = self.piece_count_total();
+        if self.round() + ENDGAME_ROUNDS > ROUND_LIMIT || pieces <= ENDGAME_MAX_PIECES {
+            Phase::Endgame
+        } else if self.round() <= OPENING_ROUND_LIMIT && pieces >= OPENING_MIN_PIECES {
+            Phase::Opening
+        } else {
+            Phase::Middlegame
+        }
+    }
+
     /// Checks whether the game is over.
     pub fn is_over(&self) -> bool {
-        self.turn % 2 == 0
-            && (self.round() > ROUND_LIMIT || self.ambers.iter().any(|(_, &v)| v >= 2))
+        self.terminal_reason().is_some()
+    }
+
+    /// Determines why the game is over, if it is. Ambers and the
+    /// round limit are only checked at the end of a round, since the
+    /// second team always gets to respond. A team without moves passes
+    /// (see `Move::Skip`), so a position in which neither team can move
+    /// ends the game immediately rather than being passed back and forth
+    /// until the round limit.
+    pub fn terminal_reason(&self) -> Option<TerminalReason> {
+        if self.turn.is_multiple_of(2) {
+            if self.ambers.values().any(|&v| v >= 2) {
+                return Some(TerminalReason::AmberWin);
+            }
+            if self.round() > ROUND_LIMIT {
+                return Some(TerminalReason::TurnLimit);
+            }
+        }
+        if [Team::One, Team::Two]
+            .into_iter()
+            .all(|team| self.moves_for(team).next().is_none())
+        {
+            return Some(TerminalReason::NoMoves);
+        }
+        None
+    }
+
+    /// Determines the outcome once the game is over: The team with
+    /// more ambers wins, otherwise the game is a draw. The server's
+    /// further tie-breaks (e.g. by advanced pieces) are not modelled.
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        if !self.is_over() {
+            return None;
+        }
+        let (one, two) = (self.amber_count(Team::One), self.amber_count(Team::Two));
+        Some(if one > two {
+            GameOutcome::Win(Team::One)
+        } else if two > one {
+            GameOutcome::Win(Team::Two)
+        } else {
+            GameOutcome::Draw
+        })
+    }
+
+    /// Plays uniformly random moves (skipping if a team cannot
+    /// move) until the game is over and returns the outcome,
+    /// e.g. for Monte Carlo tree search playouts.
+    pub fn rollout<R>(&self, rng: &mut R) -> GameOutcome
+    where
+        R: Rng + ?Sized,
+    {
+        let mut state = self.clone();
+        state.history = None;
+        state.position_hashes = None;
+        loop {
+            if let Some(outcome) = state.outcome() {
+                return outcome;
+            }
+            let m = state
+                .possible_moves()
+                .choose(rng)
+                .copied()
+                .unwrap_or(Move::Skip);
+            state.make_move(m);
+        }
+    }
+
+    /// Describes the given move including the types of the moving
+    /// and the captured piece for logging, e.g. `Robbe (0, 1) -> (2, 2)
+    /// capturing Herzmuschel`. Moves without a piece at their origin
+    /// are described by their coordinates only.
+    pub fn describe_move(&self, m: &Move) -> String {
+        match *m {
+            Move::Normal { from, to } => match self.board.get(from) {
+                Some(piece) => match self.board.get(to) {
+                    Some(captured) => format!(
+                        "{} {} capturing {}",
+                        piece.piece_type(),
+                        m,
+                        captured.piece_type()
+                    ),
+                    None => format!("{} {}", piece.piece_type(), m),
+                },
+                None => m.to_string(),
+            },
+            Move::Skip => m.to_string(),
+        }
+    }
+
+    /// Explains why the given move is illegal for the current
+    /// team, or returns `None` if it is legal.
+    pub fn explain_illegal(&self, m: &Move) -> Option<IllegalReason> {
+        if self.is_over() {
+            return Some(IllegalReason::GameOver);
+        }
+        let (from, to) = match *m {
+            Move::Normal { from, to } => (from, to),
+            Move::Skip if self.possible_moves().is_empty() => return None,
+            Move::Skip => return Some(IllegalReason::SkipNotAllowed),
+        };
+        if !Board::is_in_bounds(from) || !Board::is_in_bounds(to) {
+            return Some(IllegalReason::OffBoard);
+        }
+        let piece = match self.board.get(from) {
+            Some(piece) => piece,
+            None => return Some(IllegalReason::NoPiece),
+        };
+        if Some(piece.team()) != self.current_team() {
+            Some(IllegalReason::WrongTeam)
+        } else if !piece.possible_directions().any(|d| from + d == to) {
+            Some(IllegalReason::InvalidDirection)
+        } else if !self.board.can_move(piece, to) {
+            Some(IllegalReason::Blocked)
+        } else {
+            None
+        }
+    }
+
+    /// Determines the effect the given move would have without making it
+    /// (see `make_move`). Moves from an empty square have no effect.
+    pub fn classify(&self, m: Move) -> MoveEffect {
+        match m {
+            Move::Normal { from, to } => {
+                let piece = match self.board.get(from) {
+                    Some(piece) => piece,
+                    None => return MoveEffect::default(),
+                };
+                let captured = self.board.get(to);
+                let moved = captured.map(|c| piece.capture(c)).unwrap_or(piece);
+                let earns_amber = Board::ambers_at(moved, to) > 0;
+                MoveEffect {
+                    captured,
+                    stacked: moved.count() > 1 && !earns_amber,
+                    amber: Some(piece.team()).filter(|_| earns_amber),
+                }
+            }
+            Move::Skip => MoveEffect::default(),
+        }
+    }
+
+    /// Performs the given move on the board only, i.e. without
+    /// converting towers into ambers or advancing the turn (see
+    /// `make_move` for a full game step). The effect therefore never
+    /// reports an amber. Skips and moves from an empty square leave
+    /// the board untouched and have no effect.
+    pub fn perform(&mut self, m: Move) -> MoveEffect {
+        let mut effect = MoveEffect::default();
+        if let Move::Normal { from, to } = m {
+            if self.board.get(from).is_some() {
+                effect.captured = self.board.get(to);
+                self.board.apply_unchecked(m);
+                effect.stacked = self.board.get(to).is_some_and(|p| p.count() > 1);
+            }
+        }
+        effect
+    }
+
+    /// Makes the given move as a full game step: Performs it on the board,
+    /// credits any earned ambers to the moving team, advances the turn and
+    /// records the move (and, if tracked, the history). Moves from an empty
+    /// square leave the state untouched and have no effect.
+    pub fn make_move(&mut self, m: Move) -> MoveEffect {
+        let mut effect = MoveEffect::default();
+        if let Move::Normal { from, to } = m {
+            let team = match self.board.get(from) {
+                Some(piece) => piece.team(),
+                None => return effect,
+            };
+            effect.captured = self.board.get(to);
+            self.board.apply_unchecked(m);
+            let ambers = self.board.check_amber(to);
+            effect.stacked = self.board.get(to).is_some_and(|p| p.count() > 1);
+            for _ in 0..ambers {
+                self.add_amber(team);
+                effect.amber = Some(team);
+            }
+        }
+        self.last_move = Some(m);
+        self.turn += 1;
+        if let Some(history) = &mut self.history {
+            history.push(m);
+        }
+        if let Some(mut hashes) = self.position_hashes.take() {
+            hashes.push(self.zobrist_hash());
+            self.position_hashes = Some(hashes);
+        }
+        effect
+    }
+
+    /// Fetches the possible move leading to the state with the highest
+    /// evaluation, e.g. for a simple greedy bot. Ties are broken in favor
+    /// of the first move in `possible_moves` order. Note that the
+    /// evaluation function is applied to the state after the move,
+    /// where the opponent is on turn.
+    pub fn best_move_by<F>(&self, eval: F) -> Option<Move>
+    where
+        F: Fn(&State) -> i32,
+    {
+        let mut best: Option<(Move, i32)> = None;
+        for m in self.possible_moves() {
+            let mut child = self.clone();
+            child.make_move(m);
+            let value = eval(&child);
+            if best.is_none_or(|(_, best_value)| value > best_value) {
+                best = Some((m, value));
+            }
+        }
+        best.map(|(m, _)| m)
     }
 
-    /// Performs the given move.
-    pub fn perform(&mut self, m: Move) {
-        self.board.perform(m);
+    /// Fetches the state after the given move along with the move's
+    /// effect in a single pass, failing if the move is illegal (see
+    /// `explain_illegal`).
+    pub fn perform_move_with_effect(&self, m: Move) -> SCResult<(State, MoveEffect)> {
+        if let Some(reason) = self.explain_illegal(&m) {
+            return Err(SCError::InvalidState(format!(
+                "Cannot perform illegal move {}: {}",
+                m, reason
+            )));
+        }
+        let mut next = self.clone();
+        let effect = next.make_move(m);
+        Ok((next, effect))
     }
 
-    /// Fetches the child state after the given move.
+    /// Fetches the child state after the given move,
+    /// which is only performed on the board (see `perform`).
     pub fn child(self, m: Move) -> State {
         let mut child = self.clone();
         child.perform(m);
         child
     }
+
+    /// Fetches the state after the current team skips,
+    /// i.e. passes the turn to the opponent without moving.
+    pub fn after_skip(&self) -> State {
+        let mut next = self.clone();
+        next.make_move(Move::Skip);
+        next
+    }
+}
+
+impl State {
+    /// Encodes the position as a compact single line, similar to FEN
+    /// in chess, e.g. for bug reports. The format consists of the board
+    /// rows (by y-coordinate, see `Board::from_ascii`) separated by `/`,
+    /// where towers are followed by their height, then the turn, the
+    /// starting team (`-` if unknown) and the ambers of both teams:
+    ///
+    /// `M......h/.R2...... [...] 4 ONE 1:0`
+    ///
+    /// The last move and any history are not encoded.
+    pub fn to_compact(&self) -> String {
+        let rows: Vec<_> = BOARD_RANGE
+            .map(|y| {
+                BOARD_RANGE
+                    .map(|x| match self.board.get(Vec2::new(x, y)) {
+                        Some(piece) if piece.count() > 1 => {
+                            format!("{}{}", piece.char(), piece.count())
+                        }
+                        Some(piece) => piece.char().to_string(),
+                        None => ".".to_owned(),
+                    })
+                    .collect::<String>()
+            })
+            .collect();
+        format!(
+            "{} {} {} {}:{}",
+            rows.join("/"),
+            self.turn,
+            self.start_team
+                .map(|t| t.to_string())
+                .unwrap_or_else(|| "-".to_owned()),
+            self.amber_count(Team::One),
+            self.amber_count(Team::Two)
+        )
+    }
+
+    /// Decodes a position encoded using `to_compact`.
+    pub fn from_compact(compact: &str) -> SCResult<State> {
+        let fields: Vec<_> = compact.split_whitespace().collect();
+        let [board, turn, start_team, ambers] = fields[..] else {
+            return Err(format!("Expected 4 fields in compact state '{}'", compact).into());
+        };
+
+        let rows: Vec<_> = board.split('/').collect();
+        if rows.len() != BOARD_SIZE {
+            return Err(format!("Expected {} rows, got {}", BOARD_SIZE, rows.len()).into());
+        }
+        let mut pieces = HashMap::new();
+        for (y, row) in rows.into_iter().enumerate() {
+            let mut x = 0;
+            let mut chars = row.chars().peekable();
+            while let Some(c) = chars.next() {
+                if c != '.' {
+                    let piece = Piece::from_char(c)
+                        .ok_or_else(|| SCError::UnknownVariant(format!("Unknown piece '{}'", c)))?;
+                    let mut count = String::new();
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        count.push(digit);
+                    }
+                    let count = if count.is_empty() { 1 } else { count.parse()? };
+                    if count == 0 {
+                        return Err(format!("Invalid tower count in row '{}'", row).into());
+                    }
+                    let piece = Piece::new(piece.piece_type(), piece.team(), count);
+                    pieces.insert(Vec2::new(x, y as i32), piece);
+                }
+                x += 1;
+            }
+            if x != BOARD_SIZE as i32 {
+                return Err(format!("Expected {} squares in row '{}'", BOARD_SIZE, row).into());
+            }
+        }
+
+        let (ambers_one, ambers_two) = ambers
+            .split_once(':')
+            .ok_or_else(|| SCError::from(format!("Invalid ambers '{}'", ambers)))?;
+        Ok(State {
+            board: Board::new(pieces),
+            ambers: HashMap::from([
+                (Team::One, ambers_one.parse()?),
+                (Team::Two, ambers_two.parse()?),
+            ]),
+            turn: turn.parse()?,
+            last_move: None,
+            start_team: match start_team {
+                "-" => None,
+                team => Some(team.parse()?),
+            },
+            history: None,
+            position_hashes: None,
+        })
+    }
+}
+
+impl PartialEq for State {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board
+            && [Team::One, Team::Two]
+                .into_iter()
+                .all(|team| self.amber_count(team) == other.amber_count(team))
+            && self.turn == other.turn
+            && self.start_team == other.start_team
+    }
+}
+
+impl Eq for State {}
+
+impl Hash for State {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.zobrist_hash().hash(state);
+        for team in [Team::One, Team::Two] {
+            self.amber_count(team).hash(state);
+        }
+        self.turn.hash(state);
+        self.start_team.hash(state);
+    }
 }
 
 impl TryFrom<&Element> for State {
@@ -123,35 +714,95 @@ impl TryFrom<&Element> for State {
             ambers: elem
                 .child_by_name("ambers")?
                 .childs_by_name("entry")
-                .map(|e| {
-                    let team = Team::from_str(e.child_by_name("team")?.content())?;
-                    let piece = usize::from_str(e.child_by_name("int")?.content())?;
-                    Ok((team, piece))
-                })
+                .map(|e| Ok((e.child_text_as("team")?, e.child_text_as("int")?)))
                 .collect::<SCResult<_>>()?,
             turn: elem.attribute("turn")?.parse()?,
             last_move: elem
                 .child_by_name("lastMove")
                 .ok()
-                .and_then(|m| m.try_into().ok()),
-            start_team: elem
-                .child_by_name("startTeam")
-                .ok()
-                .and_then(|t| t.content().parse().ok()),
+                .map(Move::try_from)
+                .transpose()?,
+            start_team: elem.child_text_as("startTeam").ok(),
+            history: None,
+            position_hashes: None,
         })
     }
 }
 
+impl From<&State> for Element {
+    /// Serializes the state into a `<state>` as sent by the
+    /// server, i.e. without the history and position hashes.
+    fn from(state: &State) -> Self {
+        let mut builder = Element::new("state").attribute("turn", state.turn);
+        if let Some(team) = state.start_team {
+            builder = builder.child(Element::new("startTeam").content(team.as_ref()));
+        }
+        builder = builder.child(&state.board);
+        if let Some(m) = state.last_move {
+            builder = builder.child(match m {
+                Move::Normal { from, to } => Element::new("lastMove")
+                    .child(from.to_element("from"))
+                    .child(to.to_element("to")),
+                Move::Skip => Element::new("lastMove").attribute("class", "skip"),
+            });
+        }
+        let mut ambers: Vec<_> = state.ambers.iter().collect();
+        ambers.sort_unstable_by_key(|(team, _)| team.index());
+        builder
+            .child(
+                Element::new("ambers").childs(ambers.into_iter().map(|(team, &count)| {
+                    Element::new("entry")
+                        .child(Element::new("team").content(team.as_ref()))
+                        .child(Element::new("int").content_number(count))
+                        .build()
+                })),
+            )
+            .build()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
+    use std::{
+        cell::Cell,
+        collections::{hash_map::DefaultHasher, HashSet},
+        hash::{Hash, Hasher},
+        str::FromStr,
+    };
+
+    use rand::{rngs::StdRng, SeedableRng};
 
     use crate::{
-        game::{Board, State, Team},
+        game::{
+            Board, DefaultMoveGenerator, GameOutcome, IllegalReason, Move, MoveEffect,
+            MoveGenerator, Phase, Piece, PieceType, State, Team, TerminalReason, Vec2, BOARD_SIZE,
+            ROUND_LIMIT,
+        },
         hashmap,
         util::Element,
     };
 
+    fn sample_state() -> State {
+        State {
+            board: Board::new(hashmap![
+                Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 1),
+                Vec2::new(0, 3) => Piece::new(PieceType::Robbe, Team::One, 1),
+                Vec2::new(0, 5) => Piece::new(PieceType::Seestern, Team::One, 1),
+                Vec2::new(1, 1) => Piece::new(PieceType::Herzmuschel, Team::Two, 1),
+                Vec2::new(7, 7) => Piece::new(PieceType::Moewe, Team::Two, 1)
+            ]),
+            ambers: hashmap![
+                Team::One => 0usize,
+                Team::Two => 0usize
+            ],
+            last_move: None,
+            start_team: Some(Team::One),
+            turn: 0,
+            history: None,
+            position_hashes: None,
+        }
+    }
+
     #[test]
     fn test_parsing() {
         assert_eq!(
@@ -187,7 +838,808 @@ mod tests {
                 last_move: None,
                 start_team: None,
                 turn: 3,
+                history: None,
+                position_hashes: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parsing_skip() {
+        let state = State::try_from(
+            &Element::from_str(
+                r#"
+            <state turn="4">
+                <board><pieces></pieces></board>
+                <ambers></ambers>
+                <lastMove class="skip"></lastMove>
+            </state>
+        "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(state.last_move(), Some(Move::Skip));
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut state = sample_state();
+        state.make_move(Move::new(Vec2::new(0, 3), Vec2::new(1, 1)));
+        state.add_amber(Team::Two);
+        let xml = Element::from(&state).to_string();
+
+        assert!(xml.starts_with(r#"<state turn="1"><startTeam>ONE</startTeam><board>"#));
+        assert!(xml.contains(r#"<lastMove><from x="0" y="3"/><to x="1" y="1"/></lastMove>"#));
+        assert!(xml.ends_with(
+            "<ambers><entry><team>ONE</team><int>0</int></entry>\
+             <entry><team>TWO</team><int>1</int></entry></ambers></state>"
+        ));
+        let reparsed = State::try_from(&Element::from_str(&xml).unwrap()).unwrap();
+        assert_eq!(reparsed, state);
+        assert_eq!(reparsed.last_move(), state.last_move());
+
+        for state in [sample_state(), sample_state().after_skip()] {
+            let reparsed = State::try_from(&Element::from(&state)).unwrap();
+            assert_eq!(reparsed, state);
+            assert_eq!(reparsed.last_move(), state.last_move());
+        }
+
+        let mut anonymous = sample_state();
+        anonymous.start_team = None;
+        let xml = Element::from(&anonymous).to_string();
+        let reparsed = State::try_from(&Element::from_str(&xml).unwrap()).unwrap();
+        assert_eq!(reparsed, anonymous);
+    }
+
+    #[test]
+    fn test_after_skip() {
+        let state = State {
+            board: Board::empty(),
+            ambers: hashmap![
+                Team::One => 0usize,
+                Team::Two => 0usize
+            ],
+            last_move: None,
+            start_team: Some(Team::One),
+            turn: 4,
+            history: None,
+            position_hashes: None,
+        };
+        let skipped = state.after_skip();
+
+        assert_eq!(skipped.turn(), 5);
+        assert_eq!(skipped.current_team(), Some(Team::Two));
+        assert_eq!(skipped.last_move(), Some(Move::Skip));
+        assert_eq!(skipped.board(), state.board());
+    }
+
+    #[test]
+    fn test_possible_moves_on_edges() {
+        let edges = (0..BOARD_SIZE as i32).flat_map(|i| {
+            [
+                Vec2::new(i, 0),
+                Vec2::new(i, BOARD_SIZE as i32 - 1),
+                Vec2::new(0, i),
+                Vec2::new(BOARD_SIZE as i32 - 1, i),
+            ]
+        });
+        let types = [
+            PieceType::Herzmuschel,
+            PieceType::Moewe,
+            PieceType::Seestern,
+            PieceType::Robbe,
+        ];
+
+        for pos in edges {
+            for piece_type in types {
+                for team in [Team::One, Team::Two] {
+                    let mut state = sample_state();
+                    state.board = Board::new(hashmap![pos => Piece::new(piece_type, team, 1)]);
+                    state.start_team = Some(team);
+
+                    let moves = state.possible_moves();
+                    assert!(!moves.is_empty() || piece_type == PieceType::Herzmuschel);
+                    for m in moves {
+                        let to = m.to().unwrap();
+                        assert!(
+                            (0..BOARD_SIZE as i32).contains(&to.x)
+                                && (0..BOARD_SIZE as i32).contains(&to.y),
+                            "{:?} of {} generated out-of-bounds move {}",
+                            piece_type,
+                            team,
+                            m
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_possible_moves_deterministic() {
+        let moves = sample_state().possible_moves();
+
+        assert!(!moves.is_empty());
+        for _ in 0..10 {
+            // Every fresh state uses a differently seeded hash map
+            assert_eq!(sample_state().possible_moves(), moves);
+        }
+
+        let mut sorted = moves.clone();
+        sorted.sort_by_key(|m| (m.from(), m.to()));
+        assert_eq!(moves, sorted);
+    }
+
+    #[test]
+    fn test_make_move_quiet() {
+        let mut state = sample_state();
+        let m = Move::new(Vec2::new(0, 0), Vec2::new(0, 1));
+        let effect = state.make_move(m);
+
+        assert_eq!(effect, MoveEffect::default());
+        assert!(effect.is_quiet());
+        assert_eq!(state.board().get(Vec2::new(0, 1)).unwrap().count(), 1);
+        assert_eq!(state.turn(), 1);
+        assert_eq!(state.last_move(), Some(m));
+    }
+
+    #[test]
+    fn test_make_move_capture() {
+        let mut state = sample_state();
+        let m = Move::new(Vec2::new(0, 0), Vec2::new(1, 0));
+        state.board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 1),
+            Vec2::new(1, 0) => Piece::new(PieceType::Herzmuschel, Team::Two, 1)
+        ]);
+        let expected = MoveEffect {
+            captured: Some(Piece::new(PieceType::Herzmuschel, Team::Two, 1)),
+            stacked: true,
+            amber: None,
+        };
+
+        assert_eq!(state.classify(m), expected);
+        assert_eq!(state.make_move(m), expected);
+        assert_eq!(
+            state.board().get(Vec2::new(1, 0)),
+            Some(Piece::new(PieceType::Moewe, Team::One, 2))
+        );
+        assert_eq!(state.ambers()[&Team::One], 0);
+    }
+
+    #[test]
+    fn test_perform_board_only() {
+        let mut state = sample_state();
+        let m = Move::new(Vec2::new(0, 0), Vec2::new(1, 0));
+        state.board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 2),
+            Vec2::new(1, 0) => Piece::new(PieceType::Herzmuschel, Team::Two, 1)
+        ]);
+        let effect = state.perform(m);
+
+        assert_eq!(
+            effect,
+            MoveEffect {
+                captured: Some(Piece::new(PieceType::Herzmuschel, Team::Two, 1)),
+                stacked: true,
+                amber: None,
             }
         );
+        // The tower is neither converted nor is the turn advanced
+        assert_eq!(
+            state.board().get(Vec2::new(1, 0)),
+            Some(Piece::new(PieceType::Moewe, Team::One, 3))
+        );
+        assert_eq!(state.ambers()[&Team::One], 0);
+        assert_eq!(state.turn(), 0);
+        assert_eq!(state.last_move(), None);
+    }
+
+    #[test]
+    fn test_empty_origin() {
+        let mut state = sample_state();
+        let m = Move::new(Vec2::new(3, 3), Vec2::new(3, 4));
+
+        assert_eq!(state.classify(m), MoveEffect::default());
+        assert_eq!(m.ordering_key(&state), 0);
+        assert_eq!(state.perform(m), MoveEffect::default());
+        assert_eq!(state.make_move(m), MoveEffect::default());
+        assert_eq!(state.board(), sample_state().board());
+        assert_eq!(state.turn(), 0);
+    }
+
+    #[test]
+    fn test_add_amber() {
+        let mut state = sample_state();
+        state.ambers.clear();
+
+        state.add_amber(Team::One);
+        assert_eq!(state.ambers()[&Team::One], 1);
+        assert!(!state.ambers().contains_key(&Team::Two));
+        state.add_amber(Team::One);
+        assert_eq!(state.ambers()[&Team::One], 2);
+    }
+
+    #[test]
+    fn test_make_move_amber() {
+        let mut state = sample_state();
+        let m = Move::new(Vec2::new(0, 0), Vec2::new(1, 0));
+        state.board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 2),
+            Vec2::new(1, 0) => Piece::new(PieceType::Herzmuschel, Team::Two, 1)
+        ]);
+        let expected = MoveEffect {
+            captured: Some(Piece::new(PieceType::Herzmuschel, Team::Two, 1)),
+            stacked: false,
+            amber: Some(Team::One),
+        };
+
+        assert_eq!(state.classify(m), expected);
+        assert_eq!(state.make_move(m), expected);
+        assert_eq!(state.board().get(Vec2::new(1, 0)), None);
+        assert_eq!(state.ambers()[&Team::One], 1);
+    }
+
+    #[test]
+    fn test_history() {
+        let first = Move::new(Vec2::new(0, 0), Vec2::new(0, 1));
+        let second = Move::new(Vec2::new(7, 7), Vec2::new(7, 6));
+
+        let mut state = sample_state();
+        state.make_move(first);
+        assert_eq!(state.history(), None);
+
+        let mut state = sample_state().with_history();
+        assert_eq!(state.history(), Some(&[][..]));
+        state.make_move(first);
+        state.make_move(second);
+        assert_eq!(state.history(), Some(&[first, second][..]));
+    }
+
+    #[test]
+    fn test_is_quiet() {
+        // The robbe can capture the herzmuschel
+        let mut state = sample_state();
+        assert!(!state.is_quiet());
+
+        state.board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 1),
+            Vec2::new(0, 5) => Piece::new(PieceType::Seestern, Team::One, 1),
+            Vec2::new(1, 1) => Piece::new(PieceType::Herzmuschel, Team::Two, 1)
+        ]);
+        assert!(state.is_quiet());
+
+        // Reaching the opponent's start line earns an amber
+        state.board = Board::new(hashmap![
+            Vec2::new(6, 5) => Piece::new(PieceType::Seestern, Team::One, 1)
+        ]);
+        assert!(!state.is_quiet());
+    }
+
+    #[test]
+    fn test_moves_by_origin() {
+        let mut state = sample_state();
+        state.board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Herzmuschel, Team::One, 1),
+            Vec2::new(3, 3) => Piece::new(PieceType::Herzmuschel, Team::One, 1),
+            Vec2::new(7, 7) => Piece::new(PieceType::Herzmuschel, Team::Two, 1)
+        ]);
+        let moves = state.moves_by_origin();
+
+        assert_eq!(moves.len(), 2);
+        assert_eq!(
+            moves[&Vec2::new(0, 0)],
+            vec![Move::new(Vec2::new(0, 0), Vec2::new(1, 1))]
+        );
+        assert_eq!(
+            moves[&Vec2::new(3, 3)],
+            vec![
+                Move::new(Vec2::new(3, 3), Vec2::new(4, 2)),
+                Move::new(Vec2::new(3, 3), Vec2::new(4, 4))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_capture_moves() {
+        let mut state = sample_state();
+        state.board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 1),
+            Vec2::new(0, 3) => Piece::new(PieceType::Robbe, Team::One, 1),
+            Vec2::new(6, 5) => Piece::new(PieceType::Seestern, Team::One, 1),
+            Vec2::new(1, 0) => Piece::new(PieceType::Herzmuschel, Team::Two, 1),
+            Vec2::new(1, 1) => Piece::new(PieceType::Herzmuschel, Team::Two, 1)
+        ]);
+
+        assert_eq!(
+            state.capture_moves(),
+            vec![
+                Move::new(Vec2::new(0, 0), Vec2::new(1, 0)),
+                Move::new(Vec2::new(0, 3), Vec2::new(1, 1)),
+                Move::new(Vec2::new(6, 5), Vec2::new(7, 4)),
+                Move::new(Vec2::new(6, 5), Vec2::new(7, 5)),
+                Move::new(Vec2::new(6, 5), Vec2::new(7, 6)),
+            ]
+        );
+        assert!(state.capture_moves().len() < state.possible_moves().len());
+    }
+
+    #[test]
+    fn test_team_to_move_after() {
+        let mut state = sample_state();
+        let quiet = Move::new(Vec2::new(0, 0), Vec2::new(0, 1));
+        assert_eq!(state.team_to_move_after(quiet), Some(Team::Two));
+        let mut next = state.clone();
+        next.make_move(quiet);
+        assert_eq!(next.current_team(), Some(Team::Two));
+
+        state.board = Board::new(hashmap![
+            Vec2::new(6, 5) => Piece::new(PieceType::Seestern, Team::One, 1)
+        ]);
+        let amber = Move::new(Vec2::new(6, 5), Vec2::new(7, 5));
+        assert_eq!(state.classify(amber).amber, Some(Team::One));
+        assert_eq!(state.team_to_move_after(amber), Some(Team::Two));
+        state.make_move(amber);
+        assert_eq!(state.current_team(), Some(Team::Two));
+    }
+
+    #[test]
+    fn test_threatened_pieces() {
+        // The herzmuschel at (1, 1) attacks (0, 0) and (0, 2) from Team Two's perspective
+        let state = sample_state();
+        assert_eq!(state.threatened_pieces(Team::One), vec![Vec2::new(0, 0)]);
+        assert_eq!(state.threatened_pieces(Team::Two), vec![Vec2::new(1, 1)]);
+
+        let mut state = sample_state();
+        state.board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 1),
+            Vec2::new(7, 7) => Piece::new(PieceType::Moewe, Team::Two, 1)
+        ]);
+        assert!(state.threatened_pieces(Team::One).is_empty());
+    }
+
+    #[test]
+    fn test_repetition_draw() {
+        let shuffle = [
+            Move::new(Vec2::new(0, 0), Vec2::new(0, 1)),
+            Move::new(Vec2::new(7, 7), Vec2::new(7, 6)),
+            Move::new(Vec2::new(0, 1), Vec2::new(0, 0)),
+            Move::new(Vec2::new(7, 6), Vec2::new(7, 7)),
+        ];
+
+        let mut state = sample_state().with_history();
+        assert_eq!(state.repetition_count(), 1);
+        for i in 1..=2 {
+            assert!(!state.is_repetition_draw());
+            for m in shuffle {
+                state.make_move(m);
+            }
+            assert_eq!(state.repetition_count(), i + 1);
+        }
+        assert!(state.is_repetition_draw());
+
+        // Without history, repetitions are not tracked
+        let mut state = sample_state();
+        for m in shuffle.iter().cycle().take(8) {
+            state.make_move(*m);
+        }
+        assert_eq!(state.repetition_count(), 1);
+        assert!(!state.is_repetition_draw());
+    }
+
+    #[test]
+    fn test_game_phase() {
+        let types = [
+            PieceType::Robbe,
+            PieceType::Moewe,
+            PieceType::Seestern,
+            PieceType::Herzmuschel,
+        ];
+        let mut state = sample_state();
+        state.board = Board::new(
+            (0..BOARD_SIZE as i32)
+                .flat_map(|y| {
+                    let piece_type = types[y as usize % types.len()];
+                    [
+                        (Vec2::new(0, y), Piece::new(piece_type, Team::One, 1)),
+                        (Vec2::new(7, 7 - y), Piece::new(piece_type, Team::Two, 1)),
+                    ]
+                })
+                .collect::<std::collections::HashMap<_, _>>(),
+        );
+        assert_eq!(state.piece_count_total(), 16);
+        assert_eq!(state.game_phase(), Phase::Opening);
+
+        state.turn = 20;
+        assert_eq!(state.game_phase(), Phase::Middlegame);
+
+        let mut state = sample_state();
+        state.turn = 52;
+        assert_eq!(state.piece_count_total(), 5);
+        assert_eq!(state.game_phase(), Phase::Endgame);
+
+        state.turn = 0;
+        assert_eq!(state.game_phase(), Phase::Endgame);
+    }
+
+    #[test]
+    fn test_describe_move() {
+        let state = sample_state();
+        let m = |from: (i32, i32), to: (i32, i32)| {
+            state.describe_move(&Move::new(Vec2::new(from.0, from.1), Vec2::new(to.0, to.1)))
+        };
+
+        assert_eq!(m((0, 3), (1, 5)), "Robbe (0, 3) -> (1, 5)");
+        assert_eq!(m((0, 0), (1, 0)), "Moewe (0, 0) -> (1, 0)");
+        assert_eq!(
+            m((0, 3), (1, 1)),
+            "Robbe (0, 3) -> (1, 1) capturing Herzmuschel"
+        );
+        assert_eq!(m((3, 3), (3, 4)), "(3, 3) -> (3, 4)");
+        assert_eq!(state.describe_move(&Move::Skip), "Skip");
+    }
+
+    #[test]
+    fn test_explain_illegal() {
+        let mut state = sample_state();
+        state.board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 1),
+            Vec2::new(0, 1) => Piece::new(PieceType::Robbe, Team::One, 1),
+            Vec2::new(7, 7) => Piece::new(PieceType::Moewe, Team::Two, 1)
+        ]);
+        let explain = |from: (i32, i32), to: (i32, i32)| {
+            state.explain_illegal(&Move::new(Vec2::new(from.0, from.1), Vec2::new(to.0, to.1)))
+        };
+
+        assert_eq!(explain((0, 0), (1, 0)), None);
+        assert_eq!(explain((0, 0), (-1, 0)), Some(IllegalReason::OffBoard));
+        assert_eq!(explain((3, 3), (3, 4)), Some(IllegalReason::NoPiece));
+        assert_eq!(explain((7, 7), (7, 6)), Some(IllegalReason::WrongTeam));
+        assert_eq!(
+            explain((0, 0), (1, 1)),
+            Some(IllegalReason::InvalidDirection)
+        );
+        assert_eq!(explain((0, 0), (0, 1)), Some(IllegalReason::Blocked));
+        for m in state.possible_moves() {
+            assert_eq!(state.explain_illegal(&m), None);
+        }
+
+        assert_eq!(
+            state.explain_illegal(&Move::Skip),
+            Some(IllegalReason::SkipNotAllowed)
+        );
+        state.board = Board::new(hashmap![
+            Vec2::new(7, 7) => Piece::new(PieceType::Moewe, Team::Two, 1)
+        ]);
+        assert_eq!(state.explain_illegal(&Move::Skip), None);
+        state.board = Board::empty();
+        assert_eq!(
+            state.explain_illegal(&Move::Skip),
+            Some(IllegalReason::GameOver)
+        );
+
+        state.turn = 2 * ROUND_LIMIT + 2;
+        assert_eq!(
+            state.explain_illegal(&Move::new(Vec2::new(0, 0), Vec2::new(1, 0))),
+            Some(IllegalReason::GameOver)
+        );
+    }
+
+    #[test]
+    fn test_transpositions_equal() {
+        let play = |moves: [((i32, i32), (i32, i32)); 3]| {
+            let mut state = sample_state().with_history();
+            for (from, to) in moves {
+                state.make_move(Move::new(Vec2::new(from.0, from.1), Vec2::new(to.0, to.1)));
+            }
+            state
+        };
+        let a = play([((0, 0), (1, 0)), ((7, 7), (7, 6)), ((0, 5), (1, 5))]);
+        let b = play([((0, 5), (1, 5)), ((7, 7), (7, 6)), ((0, 0), (1, 0))]);
+
+        assert_ne!(a.history(), b.history());
+        assert_ne!(a.last_move(), b.last_move());
+        assert_eq!(a, b);
+        let hash = |state: &State| {
+            let mut hasher = DefaultHasher::new();
+            state.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&a), hash(&b));
+        assert_eq!(HashSet::from([a.clone(), b]).len(), 1);
+
+        let mut tracked = sample_state().with_history();
+        assert_eq!(tracked, sample_state());
+        assert_eq!(hash(&tracked), hash(&sample_state()));
+        tracked.make_move(Move::new(Vec2::new(0, 0), Vec2::new(1, 0)));
+        assert_ne!(tracked, a);
+    }
+
+    #[test]
+    fn test_with_board() {
+        let mut state = sample_state().with_history();
+        state.make_move(Move::new(Vec2::new(0, 0), Vec2::new(0, 1)));
+        let board = Board::new(hashmap![
+            Vec2::new(7, 7) => Piece::new(PieceType::Moewe, Team::Two, 1)
+        ]);
+        let modified = state.with_board(board.clone());
+
+        assert_eq!(modified.board(), &board);
+        assert_eq!(modified.with_board(state.board().clone()), state);
+        assert_eq!(modified.turn(), state.turn());
+        assert_eq!(modified.ambers(), state.ambers());
+        assert_eq!(modified.last_move(), state.last_move());
+        assert_eq!(modified.history(), state.history());
+    }
+
+    #[test]
+    fn test_best_move_by() {
+        let ambers = |s: &State| s.ambers().get(&Team::One).copied().unwrap_or(0) as i32;
+
+        let mut state = sample_state();
+        assert_eq!(
+            state.best_move_by(ambers),
+            state.possible_moves().first().copied()
+        );
+
+        state.board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 1),
+            Vec2::new(6, 5) => Piece::new(PieceType::Herzmuschel, Team::One, 1)
+        ]);
+        assert_eq!(
+            state.best_move_by(ambers),
+            Some(Move::new(Vec2::new(6, 5), Vec2::new(7, 4)))
+        );
+
+        state.board = Board::empty();
+        assert_eq!(state.best_move_by(ambers), None);
+    }
+
+    #[test]
+    fn test_from_perspective() {
+        let mut state = sample_state();
+        state.make_move(Move::new(Vec2::new(0, 3), Vec2::new(1, 1)));
+        state.ambers.insert(Team::Two, 1);
+        let flipped = state.from_perspective(Team::Two);
+
+        assert_eq!(state.from_perspective(Team::One), state);
+        assert_eq!(flipped.from_perspective(Team::Two), state);
+        assert_eq!(flipped.ambers()[&Team::One], 1);
+        assert_eq!(flipped.ambers()[&Team::Two], 0);
+        assert_eq!(state.current_team(), Some(Team::Two));
+        assert_eq!(flipped.current_team(), Some(Team::One));
+        assert_eq!(
+            flipped.last_move(),
+            Some(Move::new(Vec2::new(7, 3), Vec2::new(6, 1)))
+        );
+        let mut mirrored: Vec<_> = state
+            .possible_moves()
+            .into_iter()
+            .map(Move::mirror)
+            .collect();
+        mirrored.sort_by_key(|m| (m.from(), m.to()));
+        assert_eq!(flipped.possible_moves(), mirrored);
+    }
+
+    #[test]
+    fn test_possible_moves_with() {
+        struct CountingGenerator(Cell<usize>);
+
+        impl MoveGenerator for CountingGenerator {
+            fn generate(&self, state: &State) -> Vec<Move> {
+                self.0.set(self.0.get() + 1);
+                DefaultMoveGenerator
+                    .generate(state)
+                    .into_iter()
+                    .take(1)
+                    .collect()
+            }
+        }
+
+        let state = sample_state();
+        let generator = CountingGenerator(Cell::new(0));
+        let moves = state.possible_moves_with(&generator);
+
+        assert_eq!(generator.0.get(), 1);
+        assert_eq!(moves, state.possible_moves()[..1]);
+        assert_eq!(
+            state.possible_moves_with(&DefaultMoveGenerator),
+            state.possible_moves()
+        );
+    }
+
+    #[test]
+    fn test_material_balance() {
+        let mut state = sample_state();
+        state.board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Moewe, Team::One, 1),
+            Vec2::new(0, 3) => Piece::new(PieceType::Robbe, Team::One, 2),
+            Vec2::new(7, 0) => Piece::new(PieceType::Moewe, Team::Two, 1),
+            Vec2::new(7, 3) => Piece::new(PieceType::Robbe, Team::Two, 1)
+        ]);
+
+        assert_eq!(state.material_balance(Team::One), 3);
+        assert_eq!(state.material_balance(Team::Two), -3);
+
+        state.board = Board::empty();
+        assert_eq!(state.material_balance(Team::One), 0);
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut initial = sample_state();
+        initial.board = Board::from_ascii(
+            r#"
+            R......r
+            M......m
+            S......s
+            H......h
+            H......h
+            S......s
+            M......m
+            R......r
+        "#,
+        )
+        .unwrap();
+        let compact = initial.to_compact();
+
+        assert_eq!(
+            compact,
+            "R......r/M......m/S......s/H......h/H......h/S......s/M......m/R......r 0 ONE 0:0"
+        );
+        assert_eq!(State::from_compact(&compact).unwrap(), initial);
+
+        let mut state = sample_state();
+        state.make_move(Move::new(Vec2::new(0, 3), Vec2::new(1, 1)));
+        state.ambers.insert(Team::Two, 1);
+        state.start_team = None;
+        let compact = state.to_compact();
+        assert!(compact.starts_with("M......./.R2......"));
+        assert!(compact.ends_with(" 1 - 0:1"));
+        assert_eq!(State::from_compact(&compact).unwrap(), state);
+
+        assert!(State::from_compact("........ 0 ONE 0:0").is_err());
+        assert!(State::from_compact(&compact.replace("R2", "Q2")).is_err());
+        assert!(State::from_compact(&compact.replace("0:1", "0")).is_err());
+        assert!(State::from_compact(&compact.replace("R2", "R0")).is_err());
+
+        // Missing amber entries (e.g. from an empty `<ambers/>`) count as none
+        let mut empty_ambers = sample_state();
+        empty_ambers.ambers.clear();
+        assert_eq!(
+            State::from_compact(&empty_ambers.to_compact()).unwrap(),
+            empty_ambers
+        );
+    }
+
+    #[test]
+    fn test_legal_move_count() {
+        let mut state = sample_state();
+        assert_eq!(state.legal_move_count(), state.possible_moves().len());
+
+        for m in [
+            Move::new(Vec2::new(0, 0), Vec2::new(0, 1)),
+            Move::new(Vec2::new(7, 7), Vec2::new(7, 6)),
+            Move::new(Vec2::new(0, 3), Vec2::new(1, 1)),
+        ] {
+            state.make_move(m);
+            assert_eq!(state.legal_move_count(), state.possible_moves().len());
+        }
+
+        state.board = Board::empty();
+        assert_eq!(state.legal_move_count(), 0);
+        state.start_team = None;
+        assert_eq!(state.legal_move_count(), 0);
+    }
+
+    #[test]
+    fn test_board() {
+        let xml = Element::from_str(
+            r#"
+            <state turn="0">
+                <board>
+                    <pieces>
+                        <entry>
+                            <coordinates x="0" y="3"/>
+                            <piece type="Robbe" team="ONE" count="2"/>
+                        </entry>
+                        <entry>
+                            <coordinates x="7" y="4"/>
+                            <piece type="Seestern" team="TWO" count="1"/>
+                        </entry>
+                    </pieces>
+                </board>
+                <ambers></ambers>
+            </state>
+        "#,
+        )
+        .unwrap();
+        let state = State::try_from(&xml).unwrap();
+
+        assert_eq!(
+            state.board(),
+            &Board::try_from(xml.child_by_name("board").unwrap()).unwrap()
+        );
+        assert_eq!(
+            state.board().get(Vec2::new(0, 3)),
+            Some(Piece::new(PieceType::Robbe, Team::One, 2))
+        );
+        assert_eq!(state.board().pieces().len(), 2);
+    }
+
+    #[test]
+    fn test_perform_move_with_effect() {
+        let state = sample_state();
+        for m in state.possible_moves() {
+            let (next, effect) = state.perform_move_with_effect(m).unwrap();
+            let mut expected = state.clone();
+
+            assert_eq!(effect, state.classify(m));
+            assert_eq!(expected.make_move(m), effect);
+            assert_eq!(next, expected);
+        }
+
+        let illegal = Move::new(Vec2::new(7, 7), Vec2::new(7, 6));
+        assert!(state.perform_move_with_effect(illegal).is_err());
+    }
+
+    #[test]
+    fn test_terminal_reason() {
+        let mut state = sample_state();
+        assert_eq!(state.terminal_reason(), None);
+        assert!(!state.is_over());
+
+        state.ambers.insert(Team::Two, 2);
+        assert_eq!(state.terminal_reason(), Some(TerminalReason::AmberWin));
+        assert!(state.is_over());
+        // The other team may still respond mid-round
+        state.turn = 1;
+        assert_eq!(state.terminal_reason(), None);
+
+        let mut state = sample_state();
+        state.turn = 2 * ROUND_LIMIT + 2;
+        assert_eq!(state.terminal_reason(), Some(TerminalReason::TurnLimit));
+
+        let mut state = sample_state();
+        state.board = Board::new(hashmap![
+            Vec2::new(0, 0) => Piece::new(PieceType::Herzmuschel, Team::Two, 1),
+            Vec2::new(7, 7) => Piece::new(PieceType::Herzmuschel, Team::One, 1)
+        ]);
+        assert_eq!(state.terminal_reason(), Some(TerminalReason::NoMoves));
+        assert_eq!(state.outcome(), Some(GameOutcome::Draw));
+        state.board = Board::empty();
+        assert_eq!(state.terminal_reason(), Some(TerminalReason::NoMoves));
+
+        // A team that cannot move passes while the opponent still can
+        state.board = Board::new(hashmap![
+            Vec2::new(7, 7) => Piece::new(PieceType::Moewe, Team::Two, 1)
+        ]);
+        assert_eq!(state.terminal_reason(), None);
+        assert_eq!(state.explain_illegal(&Move::Skip), None);
+    }
+
+    #[test]
+    fn test_rollout() {
+        let mut state = sample_state();
+        state.board = Board::new(hashmap![
+            Vec2::new(6, 1) => Piece::new(PieceType::Seestern, Team::One, 1),
+            Vec2::new(6, 3) => Piece::new(PieceType::Seestern, Team::One, 1),
+            Vec2::new(6, 5) => Piece::new(PieceType::Seestern, Team::One, 1),
+            Vec2::new(7, 7) => Piece::new(PieceType::Moewe, Team::Two, 1)
+        ]);
+        state.ambers.insert(Team::One, 1);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let wins = (0..100)
+            .filter(|_| state.rollout(&mut rng) == GameOutcome::Win(Team::One))
+            .count();
+        assert!(wins > 90, "Only {} of 100 rollouts were won", wins);
+
+        // Rollouts end immediately if nobody can move
+        state.board = Board::empty();
+        assert_eq!(state.rollout(&mut rng), GameOutcome::Win(Team::One));
+        state.ambers.insert(Team::One, 0);
+        assert_eq!(state.rollout(&mut rng), GameOutcome::Draw);
     }
 }
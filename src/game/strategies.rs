@@ -0,0 +1,55 @@
+//! Proptest strategies generating random but legal game structures.
+
+use std::collections::HashMap;
+
+use proptest::collection::{hash_map, vec};
+use proptest::prelude::*;
+
+use super::{Board, Piece, PieceType, Team, Vec2, BOARD_SIZE};
+
+/// Generates an arbitrary team.
+pub fn arb_team() -> impl Strategy<Value = Team> {
+    prop_oneof![Just(Team::One), Just(Team::Two)]
+}
+
+/// Generates an arbitrary piece type.
+pub fn arb_piece_type() -> impl Strategy<Value = PieceType> {
+    prop_oneof![
+        Just(PieceType::Herzmuschel),
+        Just(PieceType::Moewe),
+        Just(PieceType::Seestern),
+        Just(PieceType::Robbe),
+    ]
+}
+
+/// Generates an arbitrary in-bounds position.
+pub fn arb_pos() -> impl Strategy<Value = Vec2> {
+    let size = BOARD_SIZE as i32;
+    (0..size, 0..size).prop_map(|(x, y)| Vec2::new(x, y))
+}
+
+/// Generates a piece of the given team that could occur in a game,
+/// i.e. a single piece or a tower of two (larger towers become ambers).
+pub fn arb_piece_of(team: Team) -> impl Strategy<Value = Piece> {
+    (arb_piece_type(), 1..=2usize).prop_map(move |(t, count)| Piece::new(t, team, count))
+}
+
+/// Generates a board with up to eight pieces per team on distinct squares.
+pub fn arb_board() -> impl Strategy<Value = Board> {
+    (
+        vec(arb_piece_of(Team::One), 0..=8),
+        vec(arb_piece_of(Team::Two), 0..=8),
+    )
+        .prop_flat_map(|(ones, twos)| {
+            let count = ones.len() + twos.len();
+            let pieces: Vec<_> = ones.into_iter().chain(twos).collect();
+            hash_map(arb_pos(), Just(()), count).prop_map(move |positions| {
+                Board::new(
+                    positions
+                        .into_keys()
+                        .zip(pieces.iter().copied())
+                        .collect::<HashMap<_, _>>(),
+                )
+            })
+        })
+}
@@ -3,6 +3,8 @@ use std::str::FromStr;
 
 use crate::util::{SCError, SCResult};
 
+use super::Vec2;
+
 /// A playing party in the game.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Team {
@@ -27,6 +29,17 @@ impl Team {
         }
     }
 
+    /// The team on turn after the given number of turns,
+    /// i.e. the starting team on even and its opponent
+    /// on odd turns.
+    pub fn from_turn(turn: usize, start_team: Team) -> Team {
+        if turn.is_multiple_of(2) {
+            start_team
+        } else {
+            start_team.opponent()
+        }
+    }
+
     /// The x-direction of the team on the board.
     pub fn direction(self) -> i32 {
         match self {
@@ -34,17 +47,53 @@ impl Team {
             Team::Two => -1,
         }
     }
+
+    /// The unit vector pointing 'forward' for the team, i.e. towards
+    /// the opponent's start line. Pieces such as the 'herzmuschel'
+    /// are defined relative to this direction.
+    pub fn forward(self) -> Vec2 {
+        Vec2::new(self.direction(), 0)
+    }
+
+    /// The name of the team's color in the official GUI.
+    pub fn color_name(self) -> &'static str {
+        match self {
+            Team::One => "Red",
+            Team::Two => "Blue",
+        }
+    }
+
+    /// The RGB components of the team's color.
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Team::One => (255, 0, 0),
+            Team::Two => (0, 0, 255),
+        }
+    }
 }
 
-impl fmt::Display for Team {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl AsRef<str> for Team {
+    /// The team's protocol token, i.e. `ONE` or `TWO`.
+    fn as_ref(&self) -> &str {
         match self {
-            Team::One => write!(f, "ONE"),
-            Team::Two => write!(f, "TWO"),
+            Team::One => "ONE",
+            Team::Two => "TWO",
         }
     }
 }
 
+impl From<Team> for String {
+    fn from(team: Team) -> Self {
+        team.as_ref().to_owned()
+    }
+}
+
+impl fmt::Display for Team {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
 impl FromStr for Team {
     type Err = SCError;
 
@@ -56,3 +105,58 @@ impl FromStr for Team {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::game::{Board, Team, Vec2};
+
+    #[test]
+    fn test_round_trip() {
+        for team in [Team::One, Team::Two] {
+            let token = team.to_string();
+            assert_eq!(Team::from_str(&token).unwrap(), team);
+            assert_eq!(team.as_ref(), token);
+            assert_eq!(String::from(team), team.to_string());
+        }
+        assert_eq!(Team::One.to_string(), "ONE");
+        assert_eq!(Team::Two.to_string(), "TWO");
+    }
+
+    #[test]
+    fn test_colors() {
+        assert_eq!(Team::One.color_name(), "Red");
+        assert_eq!(Team::One.rgb(), (255, 0, 0));
+        assert_eq!(Team::Two.color_name(), "Blue");
+        assert_eq!(Team::Two.rgb(), (0, 0, 255));
+    }
+
+    #[test]
+    fn test_forward() {
+        assert_eq!(Team::One.forward(), Vec2::new(1, 0));
+        assert_eq!(Team::Two.forward(), Vec2::new(-1, 0));
+        assert_eq!(Team::One.forward(), -1 * Team::Two.forward());
+
+        for team in [Team::One, Team::Two] {
+            let start = Board::start_line(team);
+            let goal = Board::start_line(team.opponent());
+            assert_eq!((goal - start).signum(), team.forward().x);
+        }
+    }
+
+    #[test]
+    fn test_from_turn() {
+        for (turn, start_team, expected) in [
+            (0, Team::One, Team::One),
+            (1, Team::One, Team::Two),
+            (2, Team::One, Team::One),
+            (0, Team::Two, Team::Two),
+            (1, Team::Two, Team::One),
+            (59, Team::Two, Team::One),
+            (60, Team::Two, Team::Two),
+        ] {
+            assert_eq!(Team::from_turn(turn, start_team), expected);
+        }
+    }
+}
@@ -0,0 +1,10 @@
+/// Why a game is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TerminalReason {
+    /// A team has collected two ambers at the end of a round.
+    AmberWin,
+    /// Neither team has a piece that could move.
+    NoMoves,
+    /// The round limit (see `ROUND_LIMIT`) has been exceeded.
+    TurnLimit,
+}
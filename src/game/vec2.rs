@@ -5,6 +5,8 @@ use std::{
 
 use crate::util::{Element, SCError, SCResult};
 
+use super::{BOARD_RANGE, BOARD_SIZE};
+
 /// The four vectors in cardinal direction.
 pub const CARDINALS: [Vec2; 4] = [
     Vec2 { x: -1, y: 0 },
@@ -22,7 +24,8 @@ pub const DIAGONALS: [Vec2; 4] = [
 ];
 
 /// A position on the board or 2D integer vector.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Vectors are ordered by x-, then by y-component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Vec2 {
     pub x: i32,
     pub y: i32,
@@ -41,6 +44,34 @@ impl Vec2 {
         Self { x, y }
     }
 
+    /// Iterates over all positions on the board, ordered by x, then by y.
+    pub fn all() -> impl Iterator<Item = Vec2> {
+        BOARD_RANGE.flat_map(|x| BOARD_RANGE.map(move |y| Vec2::new(x, y)))
+    }
+
+    /// Checks whether this position is on the board.
+    #[inline]
+    pub fn is_on_board(self) -> bool {
+        BOARD_RANGE.contains(&self.x) && BOARD_RANGE.contains(&self.y)
+    }
+
+    /// Mirrors this position along the board's vertical center line,
+    /// i.e. swaps the start lines of the two teams.
+    #[inline]
+    pub fn mirror(self) -> Self {
+        Self::new(BOARD_SIZE as i32 - 1 - self.x, self.y)
+    }
+
+    /// Checks whether this position is a light square of the
+    /// checkerboard pattern, where the corner (0, 0) is light, i.e.
+    /// squares with an even coordinate sum are light. The 2022 rules
+    /// do not tie movement to colors, but e.g. the 'herzmuschel'
+    /// never leaves the color it started on.
+    #[inline]
+    pub fn is_light(self) -> bool {
+        (self.x + self.y).rem_euclid(2) == 0
+    }
+
     /// The area of the rectangle spanned by this vector.
     #[inline]
     pub fn area(self) -> i32 {
@@ -58,6 +89,15 @@ impl Vec2 {
     pub fn length(self) -> f32 {
         (self.squared_length() as f32).sqrt()
     }
+
+    /// Serializes the position as an element with the given tag
+    /// name and `x`/`y` attributes, e.g. `<from x="0" y="1"/>`.
+    pub fn to_element(self, name: &str) -> Element {
+        Element::new(name)
+            .attribute("x", self.x)
+            .attribute("y", self.y)
+            .build()
+    }
 }
 
 impl Add for Vec2 {
@@ -131,11 +171,43 @@ impl TryFrom<&Element> for Vec2 {
     }
 }
 
+impl From<Vec2> for Element {
+    /// Serializes the position as `<coordinates x=".." y=".."/>`,
+    /// the shape the server uses e.g. on the board.
+    fn from(pos: Vec2) -> Self {
+        pos.to_element("coordinates")
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
+    use std::{collections::HashSet, str::FromStr};
 
-    use crate::{game::Vec2, util::Element};
+    use crate::{
+        game::{Vec2, BOARD_SIZE},
+        util::Element,
+    };
+
+    #[test]
+    fn test_all() {
+        let all: HashSet<_> = Vec2::all().collect();
+
+        assert_eq!(Vec2::all().count(), BOARD_SIZE * BOARD_SIZE);
+        assert_eq!(all.len(), BOARD_SIZE * BOARD_SIZE);
+        assert!(all.iter().all(|pos| pos.is_on_board()));
+        assert!(!Vec2::new(-1, 0).is_on_board());
+        assert!(!Vec2::new(0, 8).is_on_board());
+    }
+
+    #[test]
+    fn test_is_light() {
+        assert!(Vec2::new(0, 0).is_light());
+        assert!(Vec2::new(7, 7).is_light());
+        assert!(!Vec2::new(7, 0).is_light());
+        assert!(!Vec2::new(0, 7).is_light());
+        assert!(!Vec2::new(-1, 0).is_light());
+        assert_eq!(Vec2::all().filter(|p| p.is_light()).count(), 32);
+    }
 
     #[test]
     fn test_parsing() {
@@ -152,4 +224,16 @@ mod tests {
             Vec2::new(23, 0)
         );
     }
+
+    #[test]
+    fn test_serialization() {
+        let pos = Vec2::new(3, 5);
+        let elem = Element::from(pos);
+
+        assert_eq!(elem.to_string(), r#"<coordinates x="3" y="5"/>"#);
+        assert_eq!(Vec2::try_from(&elem).unwrap(), pos);
+        let reparsed = Element::from_str(&elem.to_string()).unwrap();
+        assert_eq!(Vec2::try_from(&reparsed).unwrap(), pos);
+        assert_eq!(pos.to_element("to").to_string(), r#"<to x="3" y="5"/>"#);
+    }
 }
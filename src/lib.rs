@@ -1,5 +1,70 @@
+#[cfg(feature = "client")]
 pub mod client;
 pub mod game;
+#[cfg(feature = "client")]
 pub mod logic;
 pub mod protocol;
 pub mod util;
+
+use std::str::FromStr;
+
+use game::State;
+use protocol::GameResult;
+use util::{Element, SCResult};
+
+/// Parses a game state from its `<state>` XML.
+pub fn parse_state(xml: &str) -> SCResult<State> {
+    State::try_from(&Element::from_str(xml)?)
+}
+
+/// Parses a game result from its `<data class="result">` XML.
+pub fn parse_game_result(xml: &str) -> SCResult<GameResult> {
+    GameResult::try_from(&Element::from_str(xml)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{game::Team, parse_game_result, parse_state};
+
+    #[test]
+    fn test_parse_state() {
+        let state = parse_state(
+            r#"
+            <state turn="3">
+                <startTeam>ONE</startTeam>
+                <board>
+                    <pieces></pieces>
+                </board>
+                <ambers>
+                    <entry>
+                        <team>ONE</team>
+                        <int>1</int>
+                    </entry>
+                </ambers>
+            </state>
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(state.turn(), 3);
+        assert_eq!(state.current_team(), Some(Team::Two));
+        assert_eq!(state.ambers()[&Team::One], 1);
+        assert!(parse_state("<state/>").is_err());
+    }
+
+    #[test]
+    fn test_parse_game_result() {
+        let result = parse_game_result(
+            r#"
+            <data class="result">
+                <definition></definition>
+                <scores></scores>
+                <winner team="TWO"/>
+            </data>
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(result.winner_team(), Some(Team::Two));
+    }
+}
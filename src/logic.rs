@@ -1,25 +1,164 @@
-use log::info;
+use std::collections::{HashMap, VecDeque};
+
 use rand::seq::SliceRandom;
 
 use crate::{
     client::SCClientDelegate,
     game::{Move, State, Team},
+    util::verbose_info,
 };
 
 /// An empty game logic structure that
 /// implements the client delegate trait
 /// and thus is responsible e.g. for picking
 /// a move when requested.
-pub struct OwnGameLogic;
+#[derive(Debug, Default)]
+pub struct OwnGameLogic {
+    cache: Option<MoveCache>,
+}
+
+impl OwnGameLogic {
+    /// Creates a new game logic without a cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new game logic that remembers the moves chosen in
+    /// the last `capacity` positions (keyed by their Zobrist hash)
+    /// and picks the same move again when a position repeats.
+    pub fn with_cache(capacity: usize) -> Self {
+        Self {
+            cache: Some(MoveCache::new(capacity)),
+        }
+    }
+}
 
 impl SCClientDelegate for OwnGameLogic {
     fn request_move(&mut self, state: &State, _my_team: Team) -> Move {
-        info!("Requested move");
+        verbose_info!("Requested move");
+        let hash = state.zobrist_hash();
+        if let Some(cached) = self.cache.as_mut().and_then(|c| c.get(hash)) {
+            verbose_info!("Reusing cached move {}", cached);
+            return cached;
+        }
         let chosen_move = *state
             .possible_moves()
             .choose(&mut rand::thread_rng())
             .expect("No move found!");
-        info!("Chose move {}", chosen_move);
+        if let Some(cache) = &mut self.cache {
+            cache.insert(hash, chosen_move);
+        }
+        verbose_info!("Chose move {}", state.describe_move(&chosen_move));
         chosen_move
     }
 }
+
+/// A bounded map from position hashes to moves that
+/// evicts the least recently used entry when full.
+#[derive(Debug)]
+struct MoveCache {
+    capacity: usize,
+    moves: HashMap<u64, Move>,
+    /// The hashes ordered from least to most recently used.
+    recency: VecDeque<u64>,
+}
+
+impl MoveCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            moves: HashMap::new(),
+            recency: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, hash: u64) -> Option<Move> {
+        let m = *self.moves.get(&hash)?;
+        self.touch(hash);
+        Some(m)
+    }
+
+    fn insert(&mut self, hash: u64, m: Move) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.moves.insert(hash, m).is_some() {
+            self.touch(hash);
+            return;
+        }
+        self.recency.push_back(hash);
+        if self.recency.len() > self.capacity {
+            if let Some(evicted) = self.recency.pop_front() {
+                self.moves.remove(&evicted);
+            }
+        }
+    }
+
+    fn touch(&mut self, hash: u64) {
+        if let Some(i) = self.recency.iter().position(|&h| h == hash) {
+            self.recency.remove(i);
+        }
+        self.recency.push_back(hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::SCClientDelegate,
+        game::{Move, Team, Vec2},
+        parse_state,
+    };
+
+    use super::{MoveCache, OwnGameLogic};
+
+    const STATE: &str = r#"
+        <state turn="0">
+            <startTeam>ONE</startTeam>
+            <board>
+                <pieces>
+                    <entry>
+                        <coordinates x="3" y="3"/>
+                        <piece type="Moewe" team="ONE" count="1"/>
+                    </entry>
+                    <entry>
+                        <coordinates x="7" y="7"/>
+                        <piece type="Moewe" team="TWO" count="1"/>
+                    </entry>
+                </pieces>
+            </board>
+            <ambers></ambers>
+        </state>
+    "#;
+
+    #[test]
+    fn test_cached_move() {
+        let state = parse_state(STATE).unwrap();
+        let mut logic = OwnGameLogic::with_cache(4);
+        let chosen = logic.request_move(&state, Team::One);
+
+        for _ in 0..20 {
+            assert_eq!(logic.request_move(&state, Team::One), chosen);
+        }
+        assert!(state.possible_moves().contains(&chosen));
+    }
+
+    #[test]
+    fn test_cache_eviction() {
+        let m = |x| Move::new(Vec2::new(x, 0), Vec2::new(x, 1));
+        let mut cache = MoveCache::new(2);
+        cache.insert(1, m(1));
+        cache.insert(2, m(2));
+        assert_eq!(cache.get(1), Some(m(1)));
+
+        // 2 is now the least recently used
+        cache.insert(3, m(3));
+        assert_eq!(cache.get(2), None);
+        assert_eq!(cache.get(1), Some(m(1)));
+        assert_eq!(cache.get(3), Some(m(3)));
+
+        let mut disabled = MoveCache::new(0);
+        disabled.insert(1, m(1));
+        assert_eq!(disabled.get(1), None);
+    }
+}
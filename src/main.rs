@@ -4,7 +4,9 @@ use simplelog::{Config, SimpleLogger};
 use socha_client_2022::client::{DebugMode, SCClient};
 use socha_client_2022::logic::OwnGameLogic;
 use std::env;
+use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
+use std::time::Duration;
 
 fn print_usage(program: &str, options: Options) {
     let brief = format!("Usage: {} [options]", program);
@@ -18,6 +20,12 @@ fn main() {
     options.optopt("h", "host", "The game server's host address", "HOST");
     options.optopt("p", "port", "The game server's port", "PORT");
     options.optopt("r", "reservation", "A game reservation", "RESERVATION");
+    options.optopt(
+        "b",
+        "bind",
+        "Optionally provides a local IP address to connect from",
+        "ADDRESS",
+    );
     options.optopt(
         "l",
         "level",
@@ -34,6 +42,11 @@ fn main() {
         "debug-writer",
         "Prints incoming XML messages to the console for debugging",
     );
+    options.optflag(
+        "n",
+        "no-sleep",
+        "Returns right after the game ends instead of waiting briefly",
+    );
     options.optflag("H", "help", "Prints usage info");
 
     let parsed_args = options
@@ -53,6 +66,9 @@ fn main() {
         .parse::<u16>()
         .expect("Invalid port.");
     let reservation = parsed_args.opt_str("reservation");
+    let local_address = parsed_args
+        .opt_str("bind")
+        .map(|ip| SocketAddr::new(IpAddr::from_str(&ip).expect("Invalid bind address."), 0));
     let level = parsed_args.opt_str("level").unwrap_or("Info".to_owned());
 
     // Setup logging
@@ -67,7 +83,17 @@ fn main() {
         debug_reader: parsed_args.opt_present("debug-reader"),
         debug_writer: parsed_args.opt_present("debug-writer"),
     };
-    let mut client = SCClient::new(OwnGameLogic, debug_mode, reservation);
+    let shutdown_delay = if parsed_args.opt_present("no-sleep") {
+        Duration::ZERO
+    } else {
+        Duration::from_secs(2)
+    };
+    let mut client = SCClient::builder(OwnGameLogic::new())
+        .debug_mode(debug_mode)
+        .reservation(reservation)
+        .shutdown_delay(shutdown_delay)
+        .local_address(local_address)
+        .build();
 
     let _result = client
         .connect(&host, port)
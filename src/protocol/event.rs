@@ -15,6 +15,9 @@ pub enum Event {
         room_id: String,
         payload: EventPayload,
     },
+    /// A top-level error packet, e.g. in response to a malformed
+    /// request. Unlike errors in a room, these are fatal.
+    Error { message: String },
 }
 
 impl TryFrom<&Element> for Event {
@@ -23,16 +26,80 @@ impl TryFrom<&Element> for Event {
     fn try_from(elem: &Element) -> SCResult<Self> {
         match elem.name() {
             "joined" => Ok(Self::Joined {
-                room_id: elem.attribute("roomId")?.to_owned(),
+                room_id: room_id(elem)?,
             }),
             "left" => Ok(Self::Left {
-                room_id: elem.attribute("roomId")?.to_owned(),
+                room_id: room_id(elem)?,
             }),
             "room" => Ok(Self::Room {
                 room_id: elem.attribute("roomId")?.to_owned(),
                 payload: elem.child_by_name("data")?.try_into()?,
             }),
+            "errorpacket" | "sc.protocol.ErrorPacket" => Ok(Self::Error {
+                message: elem
+                    .attribute("message")
+                    .unwrap_or_else(|_| elem.content())
+                    .to_owned(),
+            }),
             _ => Err(SCError::UnknownElement(elem.clone())),
         }
     }
 }
+
+/// Fetches the room id of a `<joined>`/`<left>` message, which depending
+/// on the server version is either a `roomId` attribute or a `<roomId>` child.
+fn room_id(elem: &Element) -> SCResult<String> {
+    match elem.attribute("roomId") {
+        Ok(room_id) => Ok(room_id.to_owned()),
+        Err(_) => Ok(elem.child_text("roomId")?.to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{protocol::Event, util::Element};
+
+    fn parse(xml: &str) -> Event {
+        Event::try_from(&Element::from_str(xml).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parsing_error_packet() {
+        for xml in [
+            r#"<sc.protocol.ErrorPacket message="Unknown room"/>"#,
+            r#"<errorpacket message="Unknown room"><originalRequest class="joinRoom"/></errorpacket>"#,
+        ] {
+            match parse(xml) {
+                Event::Error { message } => assert_eq!(message, "Unknown room"),
+                e => panic!("Expected error, got {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parsing_room_id() {
+        for xml in [
+            r#"<joined roomId="abc" />"#,
+            r#"<joined><roomId>abc</roomId></joined>"#,
+        ] {
+            match parse(xml) {
+                Event::Joined { room_id } => assert_eq!(room_id, "abc"),
+                e => panic!("Expected joined, got {:?}", e),
+            }
+        }
+
+        for xml in [
+            r#"<left roomId="abc" />"#,
+            r#"<left><roomId>abc</roomId></left>"#,
+        ] {
+            match parse(xml) {
+                Event::Left { room_id } => assert_eq!(room_id, "abc"),
+                e => panic!("Expected left, got {:?}", e),
+            }
+        }
+
+        assert!(Event::try_from(&Element::from_str("<left />").unwrap()).is_err());
+    }
+}
@@ -18,6 +18,33 @@ pub enum EventPayload {
     MoveRequest,
     /// A game result.
     GameResult(GameResult),
+    /// Notifies the client that the game was paused (`true`)
+    /// or unpaused (`false`), e.g. by an administrator.
+    Paused(bool),
+}
+
+impl EventPayload {
+    /// Fetches the state if this is a memento.
+    pub fn as_state(&self) -> Option<&State> {
+        match self {
+            Self::Memento(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Fetches the result if this is a game result.
+    pub fn as_result(&self) -> Option<&GameResult> {
+        match self {
+            Self::GameResult(result) => Some(result),
+            _ => None,
+        }
+    }
+
+    /// Checks whether this is a request to perform a move.
+    #[inline]
+    pub fn is_move_request(&self) -> bool {
+        matches!(self, Self::MoveRequest)
+    }
 }
 
 impl fmt::Display for EventPayload {
@@ -35,6 +62,7 @@ impl fmt::Display for EventPayload {
                     .map(|w| format!("{}", w.team()))
                     .unwrap_or_else(|| "none".to_owned())
             ),
+            Self::Paused(paused) => write!(f, "Paused ({})", paused),
         }
     }
 }
@@ -48,8 +76,91 @@ impl TryFrom<&Element> for EventPayload {
             "memento" => Ok(Self::Memento(elem.child_by_name("state")?.try_into()?)),
             "moveRequest" => Ok(Self::MoveRequest),
             "result" => Ok(Self::GameResult(elem.try_into()?)),
+            "paused" => Ok(Self::Paused(
+                elem.attribute("paused").map_or(Ok(true), str::parse)?,
+            )),
+            "unpaused" => Ok(Self::Paused(false)),
             "error" => Err(SCError::ServerError(elem.attribute("message")?.to_owned())),
             _ => Err(SCError::UnknownElement(elem.clone())),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, str::FromStr};
+
+    use crate::{
+        game::Team,
+        protocol::{EventPayload, GameResult, ScoreDefinition},
+        util::Element,
+    };
+
+    #[test]
+    fn test_parsing_memento_with_extra_childs() {
+        let payload = EventPayload::try_from(
+            &Element::from_str(
+                r#"
+            <data class="memento">
+                <lastMoveInfo turn="2" />
+                <state turn="3">
+                    <board>
+                        <pieces></pieces>
+                    </board>
+                    <ambers></ambers>
+                </state>
+                <comment>benign</comment>
+            </data>
+        "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(payload.as_state().map(|s| s.turn()), Some(3));
+    }
+
+    #[test]
+    fn test_accessors() {
+        let state = EventPayload::try_from(
+            &Element::from_str(
+                r#"<data class="memento"><state turn="3"><board><pieces/></board><ambers/></state></data>"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let result = GameResult::new(ScoreDefinition::default(), HashMap::new(), None);
+        let payloads = [
+            EventPayload::Welcome(Team::One),
+            state,
+            EventPayload::MoveRequest,
+            EventPayload::GameResult(result.clone()),
+            EventPayload::Paused(true),
+        ];
+
+        let states: Vec<_> = payloads.iter().map(|p| p.as_state().is_some()).collect();
+        assert_eq!(states, [false, true, false, false, false]);
+        assert_eq!(payloads[1].as_state().unwrap().turn(), 3);
+
+        let results: Vec<_> = payloads.iter().map(|p| p.as_result()).collect();
+        assert_eq!(results, [None, None, None, Some(&result), None]);
+
+        let requests: Vec<_> = payloads.iter().map(|p| p.is_move_request()).collect();
+        assert_eq!(requests, [false, false, true, false, false]);
+    }
+
+    #[test]
+    fn test_parsing_paused() {
+        for (xml, expected) in [
+            (r#"<data class="paused" />"#, true),
+            (r#"<data class="paused" paused="true" />"#, true),
+            (r#"<data class="paused" paused="false" />"#, false),
+            (r#"<data class="unpaused" />"#, false),
+        ] {
+            match EventPayload::try_from(&Element::from_str(xml).unwrap()).unwrap() {
+                EventPayload::Paused(paused) => assert_eq!(paused, expected),
+                payload => panic!("Expected pause, got {}", payload),
+            }
+        }
+    }
+}
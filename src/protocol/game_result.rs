@@ -1,8 +1,11 @@
 use std::collections::HashMap;
 
-use crate::util::{Element, SCError, SCResult};
+use crate::{
+    game::Team,
+    util::{Element, SCError, SCResult},
+};
 
-use super::{Player, Score, ScoreDefinition};
+use super::{MatchOutcome, Player, Score, ScoreDefinition};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GameResult {
@@ -25,6 +28,8 @@ impl GameResult {
         }
     }
 
+    /// The labels of the score parts, which are empty if
+    /// the server omitted the `<definition>`.
     #[inline]
     pub fn definition(&self) -> &ScoreDefinition {
         &self.definition
@@ -35,10 +40,86 @@ impl GameResult {
         &self.scores
     }
 
+    /// The scores keyed by team rather than player, since
+    /// players are not always named consistently.
+    pub fn scores_by_team(&self) -> HashMap<Team, Score> {
+        self.scores
+            .iter()
+            .map(|(player, score)| (player.team(), score.clone()))
+            .collect()
+    }
+
     #[inline]
     pub fn winner(&self) -> &Option<Player> {
         &self.winner
     }
+
+    /// The winning team, if any. Prefer comparing this over `winner`,
+    /// since the server's `<winner>` usually omits the player's name.
+    #[inline]
+    pub fn winner_team(&self) -> Option<Team> {
+        self.winner.as_ref().map(|w| w.team())
+    }
+
+    /// The player that did not win, if there is a winner.
+    pub fn loser(&self) -> Option<&Player> {
+        let winner = self.winner.as_ref()?;
+        self.scores.keys().find(|p| p.team() != winner.team())
+    }
+
+    /// Whether the game ended without a winner.
+    #[inline]
+    pub fn is_draw(&self) -> bool {
+        self.winner.is_none()
+    }
+
+    /// How the game ended from the given team's perspective.
+    pub fn outcome_for(&self, team: Team) -> MatchOutcome {
+        match self.winner_team() {
+            Some(winner) if winner == team => MatchOutcome::Win,
+            Some(_) => MatchOutcome::Loss,
+            None => MatchOutcome::Draw,
+        }
+    }
+
+    /// Formats a human-readable, multi-line report listing each
+    /// player's scores (labeled by the definition) and the winner.
+    pub fn report(&self) -> String {
+        let mut entries: Vec<_> = self.scores.iter().collect();
+        entries.sort_by_key(|(player, _)| player.team().index());
+
+        let mut lines: Vec<_> = entries
+            .into_iter()
+            .map(|(player, score)| {
+                let parts = self
+                    .definition
+                    .fragments()
+                    .iter()
+                    .zip(score.parts())
+                    .map(|(fragment, part)| format!("{}: {}", fragment.name(), part))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let reason = if score.reason().is_empty() {
+                    String::new()
+                } else {
+                    format!(" - {}", score.reason())
+                };
+                format!(
+                    "{} ({}): {} [{}{}]",
+                    player.name().unwrap_or("unnamed"),
+                    player.team(),
+                    parts,
+                    score.cause(),
+                    reason
+                )
+            })
+            .collect();
+        lines.push(match &self.winner {
+            Some(winner) => format!("Winner: {}", winner.team()),
+            None => "Draw".to_owned(),
+        });
+        lines.join("\n")
+    }
 }
 
 impl TryFrom<&Element> for GameResult {
@@ -46,7 +127,13 @@ impl TryFrom<&Element> for GameResult {
 
     fn try_from(elem: &Element) -> SCResult<Self> {
         Ok(Self {
-            definition: elem.child_by_name("definition")?.try_into()?,
+            // Abbreviated results may omit the definition
+            definition: elem
+                .child_by_name("definition")
+                .ok()
+                .map(ScoreDefinition::try_from)
+                .transpose()?
+                .unwrap_or_default(),
             scores: elem
                 .child_by_name("scores")?
                 .childs_by_name("entry")
@@ -72,63 +159,138 @@ mod tests {
         game::Team,
         hashmap,
         protocol::{
-            GameResult, Player, Score, ScoreAggregation, ScoreCause, ScoreDefinition,
+            GameResult, MatchOutcome, Player, Score, ScoreAggregation, ScoreCause, ScoreDefinition,
             ScoreDefinitionFragment,
         },
         util::Element,
     };
 
+    const SAMPLE_RESULT: &str = r#"
+        <data class="result">
+            <definition>
+                <fragment name="Siegpunkte">
+                    <aggregation>SUM</aggregation>
+                    <relevantForRanking>true</relevantForRanking>
+                </fragment>
+                <fragment name="∅ Punkte">
+                    <aggregation>AVERAGE</aggregation>
+                    <relevantForRanking>true</relevantForRanking>
+                </fragment>
+            </definition>
+            <scores>
+                <entry>
+                    <player name="rad" team="ONE"/>
+                    <score cause="REGULAR" reason="">
+                        <part>2</part>
+                        <part>27</part>
+                    </score>
+                </entry>
+                <entry>
+                    <player name="blues" team="TWO"/>
+                    <score cause="LEFT" reason="Player left">
+                        <part>0</part>
+                        <part>15</part>
+                    </score>
+                </entry>
+            </scores>
+            <winner team="ONE"/>
+        </data>
+    "#;
+
+    fn sample_result() -> GameResult {
+        GameResult::try_from(&Element::from_str(SAMPLE_RESULT).unwrap()).unwrap()
+    }
+
     #[test]
     fn test_parsing() {
         assert_eq!(
-            GameResult::try_from(
-                &Element::from_str(
-                    r#"
-            <data class="result">
-                <definition>
-                    <fragment name="Siegpunkte">
-                        <aggregation>SUM</aggregation>
-                        <relevantForRanking>true</relevantForRanking>
-                    </fragment>
-                    <fragment name="∅ Punkte">
-                        <aggregation>AVERAGE</aggregation>
-                        <relevantForRanking>true</relevantForRanking>
-                    </fragment>
-                </definition>
-                <scores>
-                    <entry>
-                        <player name="rad" team="ONE"/>
-                        <score cause="REGULAR" reason="">
-                            <part>2</part>
-                            <part>27</part>
-                        </score>
-                    </entry>
-                    <entry>
-                        <player name="blues" team="TWO"/>
-                        <score cause="LEFT" reason="Player left">
-                            <part>0</part>
-                            <part>15</part>
-                        </score>
-                    </entry>
-                </scores>
-                <winner team="ONE"/>
-            </data>
-        "#
-                )
-                .unwrap()
-            )
-            .unwrap(),
+            sample_result(),
             GameResult::new(
                 ScoreDefinition::new([
                     ScoreDefinitionFragment::new("Siegpunkte", ScoreAggregation::Sum, true),
                     ScoreDefinitionFragment::new("∅ Punkte", ScoreAggregation::Average, true),
                 ]),
                 hashmap![
-                    Player::new(Some("rad"), Team::One) => Score::new(ScoreCause::Regular, "", [2, 27]),
-                    Player::new(Some("blues"), Team::Two) => Score::new(ScoreCause::Left, "Player left", [0, 15])
+                    Player::named("rad", Team::One) => Score::new(ScoreCause::Regular, "", [2, 27]),
+                    Player::named("blues", Team::Two) => Score::new(ScoreCause::Left, "Player left", [0, 15])
                 ],
-                Some(Player::new(None, Team::One))
+                Some(Player::anonymous(Team::One))
             )
         );
     }
+
+    #[test]
+    fn test_parsing_without_definition() {
+        let mut elem = Element::from_str(SAMPLE_RESULT).unwrap();
+        elem.remove_childs_by_name("definition");
+        let result = GameResult::try_from(&elem).unwrap();
+
+        assert_eq!(result.definition(), &ScoreDefinition::default());
+        assert!(result.definition().fragments().is_empty());
+        assert_eq!(result.scores(), sample_result().scores());
+        assert_eq!(result.winner_team(), Some(Team::One));
+    }
+
+    #[test]
+    fn test_loser() {
+        let result = sample_result();
+
+        assert_eq!(result.loser(), Some(&Player::named("blues", Team::Two)));
+        assert!(!result.is_draw());
+    }
+
+    #[test]
+    fn test_report() {
+        let report = sample_result().report();
+
+        assert_eq!(
+            report,
+            [
+                "rad (ONE): Siegpunkte: 2, ∅ Punkte: 27 [REGULAR]",
+                "blues (TWO): Siegpunkte: 0, ∅ Punkte: 15 [LEFT - Player left]",
+                "Winner: ONE",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_scores_by_team() {
+        let scores = sample_result().scores_by_team();
+
+        assert_eq!(scores.len(), 2);
+        assert_eq!(
+            scores[&Team::One],
+            Score::new(ScoreCause::Regular, "", [2, 27])
+        );
+        assert_eq!(
+            scores[&Team::Two],
+            Score::new(ScoreCause::Left, "Player left", [0, 15])
+        );
+    }
+
+    #[test]
+    fn test_winner_team() {
+        let parsed = sample_result();
+        let constructed = GameResult::new(
+            parsed.definition().clone(),
+            parsed.scores().clone(),
+            Some(Player::named("rad", Team::One)),
+        );
+
+        assert_ne!(parsed.winner(), constructed.winner());
+        assert_eq!(parsed.winner_team(), constructed.winner_team());
+        assert_eq!(parsed.winner_team(), Some(Team::One));
+    }
+
+    #[test]
+    fn test_outcome_for() {
+        let won = sample_result();
+        assert_eq!(won.outcome_for(Team::One), MatchOutcome::Win);
+        assert_eq!(won.outcome_for(Team::Two), MatchOutcome::Loss);
+
+        let drawn = GameResult::new(won.definition().clone(), won.scores().clone(), None);
+        assert_eq!(drawn.outcome_for(Team::One), MatchOutcome::Draw);
+        assert_eq!(drawn.outcome_for(Team::Two), MatchOutcome::Draw);
+    }
 }
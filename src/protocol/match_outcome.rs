@@ -0,0 +1,19 @@
+use std::fmt;
+
+/// How a game ended from a single player's perspective.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchOutcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+impl fmt::Display for MatchOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Win => write!(f, "Win"),
+            Self::Loss => write!(f, "Loss"),
+            Self::Draw => write!(f, "Draw"),
+        }
+    }
+}
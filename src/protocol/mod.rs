@@ -1,6 +1,7 @@
 mod event;
 mod event_payload;
 mod game_result;
+mod match_outcome;
 mod player;
 mod request;
 mod request_payload;
@@ -13,6 +14,7 @@ mod score_definition_fragment;
 pub use event::*;
 pub use event_payload::*;
 pub use game_result::*;
+pub use match_outcome::*;
 pub use player::*;
 pub use request::*;
 pub use request_payload::*;
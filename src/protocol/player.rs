@@ -18,9 +18,22 @@ impl Player {
         }
     }
 
+    /// Creates a player without a name, e.g. as the
+    /// server sends for the `<winner>` of a game.
+    #[inline]
+    pub fn anonymous(team: Team) -> Self {
+        Self::new(None, team)
+    }
+
+    /// Creates a player with the given name.
+    #[inline]
+    pub fn named(name: &str, team: Team) -> Self {
+        Self::new(Some(name), team)
+    }
+
     #[inline]
     pub fn name(&self) -> Option<&str> {
-        self.name.as_ref().map(|n| n.as_str())
+        self.name.as_deref()
     }
 
     #[inline]
@@ -74,4 +87,17 @@ mod tests {
             Player::new(None, Team::Two)
         );
     }
+
+    #[test]
+    fn test_constructors() {
+        let anonymous = Player::anonymous(Team::Two);
+        assert_eq!(anonymous.name(), None);
+        assert_eq!(anonymous.team(), Team::Two);
+        assert_eq!(anonymous, Player::new(None, Team::Two));
+
+        let named = Player::named("Alice", Team::One);
+        assert_eq!(named.name(), Some("Alice"));
+        assert_eq!(named.team(), Team::One);
+        assert_eq!(named, Player::new(Some("Alice"), Team::One));
+    }
 }
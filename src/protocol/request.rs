@@ -1,3 +1,5 @@
+use std::fmt;
+
 use crate::util::Element;
 
 use super::RequestPayload;
@@ -19,6 +21,17 @@ pub enum Request {
         room_id: String,
         payload: RequestPayload,
     },
+    /// Authenticates as an administrator, which is
+    /// required for the other administrative requests.
+    Authenticate { password: String },
+    /// Observes the room with the given id (administrative).
+    Observe { room_id: String },
+    /// Pauses or unpauses the game in the given room (administrative).
+    Pause { room_id: String, pause: bool },
+    /// Advances a paused game by a single move (administrative).
+    Step { room_id: String },
+    /// Cancels the game in the given room (administrative).
+    Cancel { room_id: String },
 }
 
 impl From<Request> for Element {
@@ -37,6 +50,100 @@ impl From<Request> for Element {
                 .attribute("roomId", room_id)
                 .child(payload)
                 .build(),
+            Request::Authenticate { password } => Element::new("authenticate")
+                .attribute("password", password)
+                .build(),
+            Request::Observe { room_id } => {
+                Element::new("observe").attribute("roomId", room_id).build()
+            }
+            Request::Pause { room_id, pause } => Element::new("pause")
+                .attribute("roomId", room_id)
+                .attribute("pause", pause)
+                .build(),
+            Request::Step { room_id } => Element::new("step").attribute("roomId", room_id).build(),
+            Request::Cancel { room_id } => {
+                Element::new("cancel").attribute("roomId", room_id).build()
+            }
         }
     }
 }
+
+/// Formats the request as its XML serialization for logging.
+/// Passwords are redacted.
+impl fmt::Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let request = match self {
+            Self::Authenticate { .. } => Self::Authenticate {
+                password: "***".to_owned(),
+            },
+            request => request.clone(),
+        };
+        write!(f, "{}", Element::from(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{
+        game::{Move, Vec2},
+        protocol::{Request, RequestPayload},
+        util::Element,
+    };
+
+    #[test]
+    fn test_serializing_move_matches_server_schema() {
+        // As sent by the reference client and accepted by the server
+        let golden = r#"<room roomId="ae3b1a8c"><data class="move"><from x="0" y="3"/><to x="2" y="4"/></data></room>"#;
+        let captured = Element::from_str(golden).unwrap();
+        let m = Move::try_from(captured.child_by_name("data").unwrap()).unwrap();
+        assert_eq!(m, Move::new(Vec2::new(0, 3), Vec2::new(2, 4)));
+
+        let request = Request::Room {
+            room_id: captured.attribute("roomId").unwrap().to_owned(),
+            payload: RequestPayload::Move(m),
+        };
+        assert_eq!(Element::from(request).to_string(), golden);
+    }
+
+    #[test]
+    fn test_serializing_admin_requests() {
+        assert_eq!(
+            format!(
+                "{}",
+                Element::from(Request::Step {
+                    room_id: "abc".to_owned()
+                })
+            ),
+            r#"<step roomId="abc"/>"#
+        );
+        assert_eq!(
+            Element::from(Request::Pause {
+                room_id: "abc".to_owned(),
+                pause: true
+            }),
+            Element::new("pause")
+                .attribute("roomId", "abc")
+                .attribute("pause", "true")
+                .build()
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let request = Request::Room {
+            room_id: "abc".to_owned(),
+            payload: RequestPayload::Move(Move::new(Vec2::new(0, 3), Vec2::new(2, 4))),
+        };
+        let display = request.to_string();
+
+        assert!(display.starts_with(r#"<room roomId="abc"><data class="move">"#));
+        assert_eq!(display, Element::from(request).to_string());
+
+        let request = Request::Authenticate {
+            password: "secret".to_owned(),
+        };
+        assert_eq!(request.to_string(), r#"<authenticate password="***"/>"#);
+    }
+}
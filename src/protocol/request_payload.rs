@@ -1,6 +1,8 @@
 use crate::{game::Move, util::Element};
 
-/// The data of a room message to the server.
+/// The data of a room message to the server. Moves are the only
+/// data the server accepts within a room, administrative messages
+/// such as pausing or stepping are top-level `Request`s instead.
 #[derive(Debug, Clone)]
 pub enum RequestPayload {
     /// A move to be performed.
@@ -1,3 +1,5 @@
+use std::ops::Index;
+
 use crate::util::{Element, SCError, SCResult};
 
 use super::ScoreCause;
@@ -33,6 +35,26 @@ impl Score {
     pub fn parts(&self) -> &Vec<i32> {
         &self.parts
     }
+
+    /// The number of parts.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    /// Whether the score has no parts.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+}
+
+impl Index<usize> for Score {
+    type Output = i32;
+
+    fn index(&self, index: usize) -> &i32 {
+        &self.parts[index]
+    }
 }
 
 impl TryFrom<&Element> for Score {
@@ -59,6 +81,17 @@ mod tests {
         util::Element,
     };
 
+    #[test]
+    fn test_index() {
+        let score = Score::new(ScoreCause::Left, "Player left", [0, 15]);
+
+        assert_eq!(score.len(), 2);
+        assert!(!score.is_empty());
+        assert_eq!(score[0], 0);
+        assert_eq!(score[1], 15);
+        assert!(Score::new(ScoreCause::Regular, "", []).is_empty());
+    }
+
     #[test]
     fn test_parsing() {
         assert_eq!(
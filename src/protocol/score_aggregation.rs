@@ -2,10 +2,34 @@ use std::{fmt, str::FromStr};
 
 use crate::util::{SCError, SCResult};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ScoreAggregation {
     Sum,
     Average,
+    Minimum,
+    Maximum,
+    /// An aggregation this client does not know about, e.g.
+    /// from a newer server. Holds the raw token.
+    Unknown(String),
+}
+
+impl ScoreAggregation {
+    /// Aggregates the given values, if the aggregation is known
+    /// and there is at least one value.
+    pub fn aggregate(&self, values: &[i32]) -> Option<f64> {
+        if values.is_empty() {
+            return None;
+        }
+        let count = values.len() as f64;
+        let values = values.iter().map(|&v| v as f64);
+        match self {
+            Self::Sum => Some(values.sum()),
+            Self::Average => Some(values.sum::<f64>() / count),
+            Self::Minimum => values.reduce(f64::min),
+            Self::Maximum => values.reduce(f64::max),
+            Self::Unknown(_) => None,
+        }
+    }
 }
 
 impl fmt::Display for ScoreAggregation {
@@ -13,10 +37,15 @@ impl fmt::Display for ScoreAggregation {
         match self {
             Self::Sum => write!(f, "SUM"),
             Self::Average => write!(f, "AVERAGE"),
+            Self::Minimum => write!(f, "MINIMUM"),
+            Self::Maximum => write!(f, "MAXIMUM"),
+            Self::Unknown(raw) => write!(f, "{}", raw),
         }
     }
 }
 
+/// Parses an aggregation, falling back to `Unknown`
+/// for unrecognized tokens rather than failing.
 impl FromStr for ScoreAggregation {
     type Err = SCError;
 
@@ -24,10 +53,45 @@ impl FromStr for ScoreAggregation {
         match s {
             "SUM" => Ok(Self::Sum),
             "AVERAGE" => Ok(Self::Average),
-            _ => Err(SCError::UnknownVariant(format!(
-                "Unknown aggregation {}",
-                s
-            ))),
+            "MINIMUM" | "MIN" => Ok(Self::Minimum),
+            "MAXIMUM" | "MAX" => Ok(Self::Maximum),
+            _ => Ok(Self::Unknown(s.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::protocol::ScoreAggregation;
+
+    #[test]
+    fn test_parsing() {
+        for (raw, expected) in [
+            ("SUM", ScoreAggregation::Sum),
+            ("AVERAGE", ScoreAggregation::Average),
+            ("MINIMUM", ScoreAggregation::Minimum),
+            ("MIN", ScoreAggregation::Minimum),
+            ("MAXIMUM", ScoreAggregation::Maximum),
+            ("MAX", ScoreAggregation::Maximum),
+            ("MEDIAN", ScoreAggregation::Unknown("MEDIAN".to_owned())),
+        ] {
+            assert_eq!(ScoreAggregation::from_str(raw).unwrap(), expected);
         }
     }
+
+    #[test]
+    fn test_aggregate() {
+        let values = [2, 27, 1];
+        assert_eq!(ScoreAggregation::Sum.aggregate(&values), Some(30.0));
+        assert_eq!(ScoreAggregation::Average.aggregate(&values), Some(10.0));
+        assert_eq!(ScoreAggregation::Minimum.aggregate(&values), Some(1.0));
+        assert_eq!(ScoreAggregation::Maximum.aggregate(&values), Some(27.0));
+        assert_eq!(
+            ScoreAggregation::Unknown("MEDIAN".to_owned()).aggregate(&values),
+            None
+        );
+        assert_eq!(ScoreAggregation::Sum.aggregate(&[]), None);
+    }
 }
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use crate::util::{SCError, SCResult};
 
@@ -13,6 +13,19 @@ pub enum ScoreCause {
     Unknown,
 }
 
+impl fmt::Display for ScoreCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Regular => write!(f, "REGULAR"),
+            Self::Left => write!(f, "LEFT"),
+            Self::RuleViolation => write!(f, "RULE_VIOLATION"),
+            Self::SoftTimeout => write!(f, "SOFT_TIMEOUT"),
+            Self::HardTimeout => write!(f, "HARD_TIMEOUT"),
+            Self::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
 impl FromStr for ScoreCause {
     type Err = SCError;
 
@@ -2,7 +2,7 @@ use crate::util::{Element, SCError, SCResult};
 
 use super::ScoreDefinitionFragment;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct ScoreDefinition {
     fragments: Vec<ScoreDefinitionFragment>,
 }
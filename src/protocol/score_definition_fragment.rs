@@ -1,3 +1,5 @@
+use std::fmt;
+
 use crate::util::{Element, SCError, SCResult};
 
 use super::ScoreAggregation;
@@ -18,33 +20,119 @@ impl ScoreDefinitionFragment {
         }
     }
 
+    /// The fragment's label, e.g. `Siegpunkte`.
     #[inline]
     pub fn name(&self) -> &str {
         self.name.as_str()
     }
 
+    /// How the fragment's values are aggregated across games.
     #[inline]
-    pub fn aggregation(&self) -> ScoreAggregation {
-        self.aggregation
+    pub fn aggregation(&self) -> &ScoreAggregation {
+        &self.aggregation
     }
 
+    /// Whether the fragment is used to rank the players.
     #[inline]
     pub fn relevant_for_ranking(&self) -> bool {
         self.relevant_for_ranking
     }
 }
 
+/// Formats the fragment as e.g. `Siegpunkte (SUM, ranked)`.
+impl fmt::Display for ScoreDefinitionFragment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}, {})",
+            self.name,
+            self.aggregation,
+            if self.relevant_for_ranking {
+                "ranked"
+            } else {
+                "unranked"
+            }
+        )
+    }
+}
+
 impl TryFrom<&Element> for ScoreDefinitionFragment {
     type Error = SCError;
 
     fn try_from(elem: &Element) -> SCResult<Self> {
         Ok(ScoreDefinitionFragment {
             name: elem.attribute("name")?.to_owned(),
-            aggregation: elem.child_by_name("aggregation")?.content().parse()?,
-            relevant_for_ranking: elem
-                .child_by_name("relevantForRanking")?
-                .content()
-                .parse()?,
+            aggregation: elem.child_text_as("aggregation")?,
+            relevant_for_ranking: parse_flexible_bool(elem.child_text("relevantForRanking")?)?,
         })
     }
 }
+
+/// Parses a boolean leniently, accepting `true`/`false`
+/// and `1`/`0` (case-insensitively), since servers
+/// are not consistent about the spelling.
+fn parse_flexible_bool(raw: &str) -> SCResult<bool> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(SCError::UnknownVariant(format!("Unknown boolean {}", raw))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{
+        protocol::{ScoreAggregation, ScoreDefinitionFragment},
+        util::{Element, SCResult},
+    };
+
+    fn parse_fragment(relevant_for_ranking: &str) -> SCResult<ScoreDefinitionFragment> {
+        ScoreDefinitionFragment::try_from(
+            &Element::from_str(&format!(
+                r#"
+            <fragment name="Siegpunkte">
+                <aggregation>SUM</aggregation>
+                <relevantForRanking>{}</relevantForRanking>
+            </fragment>
+        "#,
+                relevant_for_ranking
+            ))
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_parsing() {
+        for (raw, expected) in [
+            ("true", true),
+            ("false", false),
+            ("TRUE", true),
+            ("False", false),
+            ("1", true),
+            ("0", false),
+        ] {
+            assert_eq!(
+                parse_fragment(raw).unwrap(),
+                ScoreDefinitionFragment::new("Siegpunkte", ScoreAggregation::Sum, expected)
+            );
+        }
+
+        assert!(parse_fragment("yes").is_err());
+    }
+
+    #[test]
+    fn test_accessors() {
+        let fragment = ScoreDefinitionFragment::new("∅ Punkte", ScoreAggregation::Average, false);
+
+        assert_eq!(fragment.name(), "∅ Punkte");
+        assert_eq!(fragment.aggregation(), &ScoreAggregation::Average);
+        assert!(!fragment.relevant_for_ranking());
+        assert_eq!(fragment.to_string(), "∅ Punkte (AVERAGE, unranked)");
+        assert_eq!(
+            parse_fragment("true").unwrap().to_string(),
+            "Siegpunkte (SUM, ranked)"
+        );
+    }
+}
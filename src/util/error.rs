@@ -9,19 +9,40 @@ use super::Element;
 /// other errors (such as IO/XML errors) and
 /// can conveniently be used in conjunction with
 /// `Result`.
+///
+/// New variants may be added over time, so matches
+/// outside this crate need a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum SCError {
+    /// An error while reading or writing the stream.
     Io(IoError),
+    /// An integer in a message could not be parsed.
     ParseInt(ParseIntError),
+    /// A float in a message could not be parsed.
     ParseFloat(ParseFloatError),
+    /// A boolean in a message could not be parsed.
     ParseBool(ParseBoolError),
+    /// A message contained invalid UTF-8.
     Utf8(Utf8Error),
+    /// A message contained malformed XML.
     Xml(XmlError),
+    /// A message had an unexpected tag.
     UnknownElement(Element),
+    /// A message contained an unknown enum token.
     UnknownVariant(String),
+    /// The client got into a state it cannot continue from.
     InvalidState(String),
+    /// The server reported an error.
     ServerError(String),
+    /// The server reported a fatal, top-level error.
+    FatalServerError(String),
+    /// The stream ended unexpectedly, e.g. in the middle of a message.
     Eof,
+    /// The stream ended (or the enclosing `<protocol>` was closed)
+    /// between messages, i.e. the peer closed the connection cleanly.
+    ConnectionClosed,
+    /// Any other error.
     Custom(String),
 }
 
@@ -72,3 +93,21 @@ impl<'a> From<&'a str> for SCError {
         Self::Custom(error.to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SCError;
+
+    #[test]
+    fn test_catch_all() {
+        let describe = |error: SCError| match error {
+            SCError::Eof => "eof",
+            SCError::ServerError(_) => "server",
+            _ => "other",
+        };
+
+        assert_eq!(describe(SCError::Eof), "eof");
+        assert_eq!(describe(SCError::ServerError("x".to_owned())), "server");
+        assert_eq!(describe("custom".into()), "other");
+    }
+}
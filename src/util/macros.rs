@@ -24,3 +24,40 @@ macro_rules! hashset {
         m
     }}
 }
+
+/// Logs at debug level if the `verbose-logging` feature is enabled.
+/// Otherwise the call is compiled out, while the arguments are
+/// still type-checked (but never evaluated).
+macro_rules! verbose_debug {
+    ($($arg:tt)+) => {
+        if cfg!(feature = "verbose-logging") {
+            ::log::debug!($($arg)+)
+        }
+    };
+}
+
+/// Logs at info level if the `verbose-logging` feature is enabled,
+/// see `verbose_debug`. Only the game logic logs at this level.
+#[cfg(feature = "client")]
+macro_rules! verbose_info {
+    ($($arg:tt)+) => {
+        if cfg!(feature = "verbose-logging") {
+            ::log::info!($($arg)+)
+        }
+    };
+}
+
+/// Logs at trace level if the `verbose-logging` feature is enabled,
+/// see `verbose_debug`.
+macro_rules! verbose_trace {
+    ($($arg:tt)+) => {
+        if cfg!(feature = "verbose-logging") {
+            ::log::trace!($($arg)+)
+        }
+    };
+}
+
+pub(crate) use verbose_debug;
+#[cfg(feature = "client")]
+pub(crate) use verbose_info;
+pub(crate) use verbose_trace;
@@ -4,6 +4,6 @@ mod result;
 mod xml;
 
 pub use error::*;
-pub use macros::*;
+pub(crate) use macros::*;
 pub use result::*;
 pub use xml::*;
@@ -1,22 +1,26 @@
-use super::{SCError, SCResult};
-use log::{debug, error, info, trace, warn};
+use super::{verbose_debug, verbose_trace, SCError, SCResult};
+use log::{info, warn};
 use quick_xml::events::attributes::Attribute;
 use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
 use quick_xml::{Reader, Writer};
 use std::borrow::Cow;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, VecDeque};
 use std::convert::{TryFrom, TryInto};
 use std::fmt::{self, Debug};
-use std::io::{BufRead, Cursor, Write};
+use std::io::{self, BufRead, Write};
 use std::str::{self, FromStr};
 
 /// A deserialized, in-memory tree-representation
-/// of an XML node.
+/// of an XML node. Attributes are kept sorted by key,
+/// so serialization is deterministic.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Element {
     name: String,
     content: String,
-    attributes: HashMap<String, String>,
+    /// The byte offset into `content` at which each child element
+    /// occurs, which marks the text segment boundaries in mixed content.
+    child_offsets: Vec<usize>,
+    attributes: BTreeMap<String, String>,
     childs: Vec<Element>,
 }
 
@@ -24,19 +28,23 @@ pub struct Element {
 /// XML nodes more convenient.
 pub struct ElementBuilder<'a> {
     name: &'a str,
-    content: &'a str,
-    attributes: HashMap<String, String>,
+    content: Cow<'a, str>,
+    attributes: BTreeMap<String, String>,
     childs: Vec<Element>,
 }
 
 impl Element {
     /// Creates a new XML element builder.
-    pub fn new(name: &str) -> ElementBuilder {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(name: &str) -> ElementBuilder<'_> {
         ElementBuilder::new(name)
     }
 
     /// Deserializes an XML node tree
-    /// from the given XML event reader.
+    /// from the given XML event reader. Fails with
+    /// `ConnectionClosed` if the stream (or the enclosing
+    /// element) ends before a new node starts and with
+    /// `Eof` if it ends in the middle of a node.
     pub fn read_from<R>(reader: &mut Reader<R>) -> SCResult<Element>
     where
         R: BufRead,
@@ -47,38 +55,37 @@ impl Element {
         let element = loop {
             match reader.read_event(&mut buf) {
                 Ok(Event::Start(ref start)) => {
-                    trace!("Read start event");
+                    verbose_trace!("Read start event");
                     let node = Element::try_from(start)?;
                     node_stack.push_back(node);
                 }
                 Ok(Event::Empty(ref start)) => {
-                    trace!("Read empty event");
+                    verbose_trace!("Read empty event");
                     let node = Element::try_from(start)?;
                     if let Some(mut parent) = node_stack.pop_back() {
-                        parent.childs.push(node);
+                        parent.push_child(node);
                         node_stack.push_back(parent);
                     } else {
                         break Ok(node);
                     }
                 }
                 Ok(Event::End(ref end)) => {
-                    trace!("Read end event");
+                    verbose_trace!("Read end event");
                     if let Some(node) = node_stack.pop_back() {
                         if let Some(mut parent) = node_stack.pop_back() {
-                            parent.childs.push(node);
+                            parent.push_child(node);
                             node_stack.push_back(parent);
                         } else {
                             break Ok(node);
                         }
                     } else {
-                        error!(
-                            "Found closing element </{}> without an opening element before",
-                            str::from_utf8(end.name())?
-                        );
+                        // The enclosing element (e.g. <protocol>) was closed
+                        verbose_debug!("Read closing </{}>", str::from_utf8(end.name())?);
+                        break Err(SCError::ConnectionClosed);
                     }
                 }
                 Ok(Event::Text(ref t)) => {
-                    trace!("Read text event");
+                    verbose_trace!("Read text event");
                     let content = str::from_utf8(t)?.trim();
                     if !content.is_empty() {
                         if let Some(node) = node_stack.back_mut() {
@@ -88,16 +95,23 @@ impl Element {
                         }
                     }
                 }
+                Ok(Event::Eof) if node_stack.is_empty() => break Err(SCError::ConnectionClosed),
                 Ok(Event::Eof) => break Err(SCError::Eof),
                 Err(e) => break Err(e.into()),
                 ev => info!("Read other event: {:?}", ev),
             }
         }?;
 
-        debug!("Read {}", element);
+        verbose_debug!("Read {}", element);
         Ok(element)
     }
 
+    /// Deserializes an XML node tree from the given
+    /// UTF-8 bytes, see also the `FromStr` implementation.
+    pub fn from_bytes(bytes: &[u8]) -> SCResult<Element> {
+        Element::read_from(&mut Reader::from_reader(bytes))
+    }
+
     /// Serializes the node to an XML string using a tree traversal.
     pub fn write_to<W>(&self, writer: &mut Writer<W>) -> SCResult<()>
     where
@@ -106,7 +120,7 @@ impl Element {
         self.write_to_impl(writer)?;
         writer.inner().flush()?;
 
-        debug!("Wrote {}", self);
+        verbose_debug!("Wrote {}", self);
         Ok(())
     }
 
@@ -114,27 +128,44 @@ impl Element {
     where
         W: Write,
     {
-        let start = BytesStart::from(self);
-
-        if self.childs.is_empty() {
-            // Write self-closing tag, e.g. <Element/>
-            writer.write_event(Event::Empty(start))?;
-        } else {
-            // Write opening tag, e.g. <Element>
-            writer.write_event(Event::Start(start))?;
-
-            // Write text
-            if !self.content.is_empty() {
-                writer.write_event(Event::Text(BytesText::from_plain(self.content.as_bytes())))?;
-            }
-
-            // Write child elements
-            for child in &self.childs {
-                child.write_to(writer)?;
+        // Uses an explicit stack rather than recursion (like read_from),
+        // so that deeply nested trees cannot overflow the call stack
+        let mut stack = vec![WriteStep::Open(self)];
+
+        while let Some(step) = stack.pop() {
+            match step {
+                WriteStep::Open(node) => {
+                    let start = BytesStart::from(node);
+
+                    if node.childs.is_empty() && node.content.is_empty() {
+                        // Write self-closing tag, e.g. <Element/>
+                        writer.write_event(Event::Empty(start))?;
+                    } else {
+                        // Write opening tag, e.g. <Element>
+                        writer.write_event(Event::Start(start))?;
+
+                        // Write the text segments interleaved with the
+                        // child elements, then the closing tag
+                        stack.push(WriteStep::Close(node));
+                        let mut end = node.content.len();
+                        for (child, &offset) in node.childs.iter().zip(&node.child_offsets).rev() {
+                            stack.push(WriteStep::Text(&node.content[offset..end]));
+                            stack.push(WriteStep::Open(child));
+                            end = offset;
+                        }
+                        stack.push(WriteStep::Text(&node.content[..end]));
+                    }
+                }
+                WriteStep::Text(text) => {
+                    if !text.is_empty() {
+                        writer.write_event(Event::Text(BytesText::from_plain(text.as_bytes())))?;
+                    }
+                }
+                WriteStep::Close(node) => {
+                    // Write closing tag, e.g. </Element>
+                    writer.write_event(Event::End(BytesEnd::borrowed(node.name.as_bytes())))?;
+                }
             }
-
-            // Write closing tag, e.g. </Element>
-            writer.write_event(Event::End(BytesEnd::borrowed(self.name.as_bytes())))?;
         }
 
         Ok(())
@@ -145,11 +176,35 @@ impl Element {
         self.name.as_str()
     }
 
-    /// Fetches the node's textual contents.
+    /// Fetches the node's tag name without any namespace prefix,
+    /// e.g. `state` for `<ns2:state>`. The parser keeps prefixes
+    /// as they are, so the `*_by_local_name` lookups should be used
+    /// for messages that may contain namespaced tags.
+    pub fn local_name(&self) -> &str {
+        self.name.rsplit(':').next().unwrap_or(&self.name)
+    }
+
+    /// Fetches the node's textual contents. If the text is
+    /// interleaved with child elements, the (trimmed) segments
+    /// are concatenated, see `texts` for the individual segments.
     pub fn content(&self) -> &str {
         self.content.as_str()
     }
 
+    /// Fetches the node's (trimmed) text segments in document
+    /// order, which are separated by child elements in mixed content.
+    pub fn texts(&self) -> Vec<&str> {
+        let mut texts = Vec::new();
+        let mut start = 0;
+        for &offset in self.child_offsets.iter().chain([&self.content.len()]) {
+            if offset > start {
+                texts.push(&self.content[start..offset]);
+                start = offset;
+            }
+        }
+        texts
+    }
+
     /// Fetches an attribute's value by key.
     pub fn attribute(&self, key: &str) -> SCResult<&str> {
         self.attributes.get(key).map(|s| s.as_str()).ok_or_else(|| {
@@ -157,6 +212,11 @@ impl Element {
         })
     }
 
+    /// Fetches the node's child elements.
+    pub fn childs(&self) -> &Vec<Element> {
+        &self.childs
+    }
+
     /// Finds the first child element with the provided tag name.
     pub fn child_by_name<'a, 'n: 'a>(&'a self, name: &'n str) -> SCResult<&'a Element> {
         self.childs_by_name(name)
@@ -171,21 +231,123 @@ impl Element {
     ) -> impl Iterator<Item = &'a Element> + 'a {
         self.childs.iter().filter(move |c| c.name == name)
     }
+
+    /// Fetches the textual contents of the first child
+    /// element with the provided tag name.
+    pub fn child_text<'a, 'n: 'a>(&'a self, name: &'n str) -> SCResult<&'a str> {
+        self.child_by_name(name).map(|c| c.content())
+    }
+
+    /// Parses the textual contents of the first child
+    /// element with the provided tag name.
+    pub fn child_text_as<T>(&self, name: &str) -> SCResult<T>
+    where
+        T: FromStr,
+        SCError: From<T::Err>,
+    {
+        Ok(self.child_text(name)?.parse()?)
+    }
+
+    /// Finds the first child element with the provided
+    /// tag name, ignoring namespace prefixes.
+    pub fn child_by_local_name<'a, 'n: 'a>(&'a self, name: &'n str) -> SCResult<&'a Element> {
+        self.childs_by_local_name(name).next().ok_or_else(|| {
+            format!("No <{}> (in any namespace) found in <{}>!", name, self.name).into()
+        })
+    }
+
+    /// Fetches a list of all child elements matching the
+    /// provided tag name, ignoring namespace prefixes.
+    pub fn childs_by_local_name<'a, 'n: 'a>(
+        &'a self,
+        name: &'n str,
+    ) -> impl Iterator<Item = &'a Element> + 'a {
+        self.childs.iter().filter(move |c| c.local_name() == name)
+    }
+
+    /// Finds the first element with the provided tag name in the tree
+    /// rooted at this node (including the node itself) using a
+    /// depth-first search.
+    pub fn find_recursive(&self, name: &str) -> Option<&Element> {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            if node.name == name {
+                return Some(node);
+            }
+            stack.extend(node.childs.iter().rev());
+        }
+        None
+    }
+
+    /// Sets an attribute, replacing any previous value.
+    pub fn set_attribute(&mut self, key: impl ToString, value: impl ToString) {
+        self.attributes.insert(key.to_string(), value.to_string());
+    }
+
+    /// Removes an attribute, returning its previous value.
+    pub fn remove_attribute(&mut self, key: &str) -> Option<String> {
+        self.attributes.remove(key)
+    }
+
+    /// Removes all child elements matching the provided tag name
+    /// and returns them. The text around a removed child merges.
+    pub fn remove_childs_by_name(&mut self, name: &str) -> Vec<Element> {
+        let mut removed = Vec::new();
+        let childs = self.childs.drain(..).zip(self.child_offsets.drain(..));
+        let (mut kept, mut kept_offsets) = (Vec::new(), Vec::new());
+        for (child, offset) in childs {
+            if child.name == name {
+                removed.push(child);
+            } else {
+                kept.push(child);
+                kept_offsets.push(offset);
+            }
+        }
+        self.childs = kept;
+        self.child_offsets = kept_offsets;
+        removed
+    }
+
+    /// Appends a child element after the current text.
+    fn push_child(&mut self, child: Element) {
+        self.child_offsets.push(self.content.len());
+        self.childs.push(child);
+    }
 }
 
 impl fmt::Display for Element {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // Writes the node as XML
-        let mut writer = Writer::new(Cursor::new(Vec::new()));
-        self.write_to(&mut writer).map_err(|_| fmt::Error)?;
-        write!(
-            f,
-            "{}",
-            str::from_utf8(&writer.into_inner().into_inner()).map_err(|_| fmt::Error)?
-        )
+        // Writes the node as XML directly to the formatter
+        let mut writer = Writer::new(FmtWriter(f));
+        self.write_to_impl(&mut writer).map_err(|_| fmt::Error)
     }
 }
 
+/// A pending step of the (non-recursive) serialization.
+enum WriteStep<'a> {
+    Open(&'a Element),
+    Text(&'a str),
+    Close(&'a Element),
+}
+
+/// An adapter for writing UTF-8 bytes to a formatter. The XML writer
+/// only ever writes complete strings, so every chunk is valid UTF-8.
+struct FmtWriter<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl Write for FmtWriter<'_, '_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let s = str::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.0.write_str(s).map_err(io::Error::other)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Parses an XML node tree from a string,
+/// e.g. `"<a><b/></a>".parse::<Element>()`.
 impl FromStr for Element {
     type Err = SCError;
 
@@ -200,8 +362,8 @@ impl<'a> ElementBuilder<'a> {
     pub fn new(name: &'a str) -> Self {
         Self {
             name,
-            content: "",
-            attributes: HashMap::new(),
+            content: Cow::Borrowed(""),
+            attributes: BTreeMap::new(),
             childs: Vec::new(),
         }
     }
@@ -214,7 +376,14 @@ impl<'a> ElementBuilder<'a> {
 
     /// Sets the contents of the XML node.
     pub fn content(mut self, data: &'a str) -> Self {
-        self.content = data;
+        self.content = Cow::Borrowed(data);
+        self
+    }
+
+    /// Sets the contents of the XML node to the formatted
+    /// number (or any other displayable value), e.g. for `<int>`s.
+    pub fn content_number(mut self, n: impl fmt::Display) -> Self {
+        self.content = Cow::Owned(n.to_string());
         self
     }
 
@@ -250,9 +419,12 @@ impl<'a> ElementBuilder<'a> {
 
     /// Builds the XML node.
     pub fn build(self) -> Element {
+        let content = self.content.into_owned();
         Element {
             name: self.name.to_owned(),
-            content: self.content.to_owned(),
+            // The text precedes all children
+            child_offsets: vec![content.len(); self.childs.len()],
+            content,
             attributes: self.attributes,
             childs: self.childs,
         }
@@ -278,16 +450,16 @@ impl<'a> TryFrom<&BytesStart<'a>> for Element {
         Ok(Element {
             name: str::from_utf8(start.name())?.to_owned(),
             content: String::new(),
+            child_offsets: Vec::new(),
             attributes: start
                 .attributes()
-                .into_iter()
                 .map(|res| {
                     let attribute = res?;
                     let key = str::from_utf8(attribute.key)?.to_owned();
                     let value = str::from_utf8(&attribute.value)?.to_owned();
                     Ok((key, value))
                 })
-                .collect::<SCResult<HashMap<_, _>>>()?,
+                .collect::<SCResult<BTreeMap<_, _>>>()?,
             childs: Vec::new(),
         })
     }
@@ -306,6 +478,12 @@ impl<'a> From<&'a Element> for BytesStart<'a> {
 
 #[cfg(test)]
 mod tests {
+    use std::io::BufWriter;
+
+    use quick_xml::Writer;
+
+    use crate::game::Team;
+
     use super::Element;
 
     #[test]
@@ -323,6 +501,72 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_write_content() {
+        assert_eq!(
+            Element::new("int").content_number(5).build().to_string(),
+            "<int>5</int>"
+        );
+        assert_eq!(
+            Element::new("team").content("ONE").build().to_string(),
+            "<team>ONE</team>"
+        );
+
+        let element = Element::new("entry")
+            .child(Element::new("team").content("TWO"))
+            .child(Element::new("int").content_number(-3))
+            .build();
+        let reparsed = element.to_string().parse::<Element>().unwrap();
+        assert_eq!(reparsed.child_text_as::<i32>("int").unwrap(), -3);
+        assert_eq!(reparsed, element);
+    }
+
+    #[test]
+    fn test_deeply_nested() {
+        const DEPTH: usize = 10_000;
+        let mut element = Element::new("leaf").content("x").build();
+        for _ in 1..DEPTH {
+            element = Element::new("node").child(element).build();
+        }
+
+        let mut writer = Writer::new(Vec::new());
+        element.write_to(&mut writer).unwrap();
+        let xml = String::from_utf8(writer.into_inner()).unwrap();
+        let inner = "<node>".repeat(DEPTH - 1) + "<leaf>x</leaf>" + &"</node>".repeat(DEPTH - 1);
+        assert_eq!(xml, inner);
+
+        let reparsed = xml.parse::<Element>().unwrap();
+        assert_eq!(reparsed.to_string(), xml);
+        let leaf = reparsed.find_recursive("leaf").unwrap();
+        assert_eq!(leaf.content(), "x");
+    }
+
+    #[test]
+    fn test_display_matches_write_to() {
+        let element = Element::new("room")
+            .attribute("roomId", "a&b")
+            .child(Element::new("data").attribute("class", "move"))
+            .child(Element::new("text").content("Möwe <3"))
+            .build();
+        let mut writer = Writer::new(Vec::new());
+        element.write_to(&mut writer).unwrap();
+
+        assert_eq!(
+            element.to_string(),
+            String::from_utf8(writer.into_inner()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_to_flushes() {
+        let element = Element::new("join").build();
+        let mut writer = Writer::new(BufWriter::with_capacity(1024, Vec::new()));
+        element.write_to(&mut writer).unwrap();
+
+        // The bytes reached the underlying writer despite the buffer
+        assert_eq!(writer.inner().get_ref(), b"<join/>");
+    }
+
     #[test]
     fn test_read() {
         assert_eq!(
@@ -330,4 +574,127 @@ mod tests {
             Element::new("Test").build()
         );
     }
+
+    #[test]
+    fn test_from_bytes() {
+        let bytes: &[u8] = br#"<room roomId="abc"><data class="welcomeMessage"/></room>"#;
+        let element = Element::from_bytes(bytes).unwrap();
+
+        assert_eq!(element.name(), "room");
+        assert_eq!(element.attribute("roomId").unwrap(), "abc");
+        assert_eq!(
+            element,
+            std::str::from_utf8(bytes).unwrap().parse().unwrap()
+        );
+        assert!(Element::from_bytes(b"").is_err());
+    }
+
+    #[test]
+    fn test_local_name() {
+        let element =
+            r#"<ns1:room xmlns:ns1="a" xmlns:ns2="b"><ns2:state turn="1"/><board/></ns1:room>"#
+                .parse::<Element>()
+                .unwrap();
+
+        assert_eq!(element.name(), "ns1:room");
+        assert_eq!(element.local_name(), "room");
+        assert!(element.child_by_name("state").is_err());
+        let state = element.child_by_local_name("state").unwrap();
+        assert_eq!(state.name(), "ns2:state");
+        assert_eq!(state.attribute("turn").unwrap(), "1");
+        assert_eq!(
+            element.child_by_local_name("board").unwrap().local_name(),
+            "board"
+        );
+        assert!(element.child_by_local_name("ambers").is_err());
+    }
+
+    #[test]
+    fn test_child_text() {
+        let element = r#"<entry><team>ONE</team><int>2</int><empty/></entry>"#
+            .parse::<Element>()
+            .unwrap();
+
+        assert_eq!(element.child_text("team").unwrap(), "ONE");
+        assert_eq!(element.child_text("empty").unwrap(), "");
+        assert!(element.child_text("missing").is_err());
+
+        assert_eq!(element.child_text_as::<Team>("team").unwrap(), Team::One);
+        assert_eq!(element.child_text_as::<usize>("int").unwrap(), 2);
+        assert!(element.child_text_as::<usize>("team").is_err());
+        assert!(element.child_text_as::<usize>("missing").is_err());
+    }
+
+    #[test]
+    fn test_read_mixed_content() {
+        let element = "<a>x<b/>y</a>".parse::<Element>().unwrap();
+
+        assert_eq!(element.texts(), ["x", "y"]);
+        assert_eq!(element.content(), "xy");
+        assert_eq!("<a>xy</a>".parse::<Element>().unwrap().texts(), ["xy"]);
+        assert_eq!(
+            "<a>x</a>".parse::<Element>().unwrap(),
+            Element::new("a").content("x").build()
+        );
+    }
+
+    #[test]
+    fn test_write_mixed_content() {
+        let element = "<a>x<b/>y</a>".parse::<Element>().unwrap();
+        assert_eq!(element.to_string(), "<a>x<b/>y</a>");
+
+        let reparsed = element.to_string().parse::<Element>().unwrap();
+        assert_eq!(reparsed.texts(), ["x", "y"]);
+        assert_eq!(reparsed, element);
+        assert_ne!(reparsed, "<a>xy<b/></a>".parse::<Element>().unwrap());
+
+        // Built elements write their text before any children
+        let built = Element::new("a")
+            .content("xy")
+            .child(Element::new("b"))
+            .build();
+        assert_eq!(built.to_string(), "<a>xy<b/></a>");
+        assert_eq!(built, built.to_string().parse::<Element>().unwrap());
+
+        let mut element = "<a>x<b/>y<c/>z</a>".parse::<Element>().unwrap();
+        element.remove_childs_by_name("b");
+        assert_eq!(element.texts(), ["xy", "z"]);
+        assert_eq!(element.to_string(), "<a>xy<c/>z</a>");
+    }
+
+    #[test]
+    fn test_mutate() {
+        let mut element = r#"<entry><player name="rad" team="ONE"/><score/><score/></entry>"#
+            .parse::<Element>()
+            .unwrap();
+
+        let removed = element.remove_childs_by_name("score");
+        assert_eq!(removed.len(), 2);
+
+        let mut player = element.remove_childs_by_name("player").remove(0);
+        player.set_attribute("name", "anonymous");
+        assert_eq!(player.remove_attribute("team"), Some("ONE".to_owned()));
+        assert_eq!(player.remove_attribute("team"), None);
+
+        assert_eq!(format!("{}", element), "<entry/>");
+        assert_eq!(format!("{}", player), r#"<player name="anonymous"/>"#);
+        assert_eq!(
+            format!("{}", player).parse::<Element>().unwrap(),
+            Element::new("player")
+                .attribute("name", "anonymous")
+                .build()
+        );
+    }
+
+    #[test]
+    fn test_find_recursive() {
+        let element = r#"<a><b><c><state turn="1"/></c></b><state turn="2"/></a>"#
+            .parse::<Element>()
+            .unwrap();
+
+        let state = element.find_recursive("state").unwrap();
+        assert_eq!(state.attribute("turn").unwrap(), "1");
+        assert_eq!(element.find_recursive("a"), Some(&element));
+        assert_eq!(element.find_recursive("board"), None);
+    }
 }